@@ -0,0 +1,51 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use super::{error_impl, Backend, EnumSpec};
+
+// Derives `displaydoc::Display`, doc comments being the Display messages
+pub(crate) struct Displaydoc;
+
+impl Backend for Displaydoc {
+    fn enum_def(&self, spec: &EnumSpec) -> TokenStream2 {
+        let vis = &spec.config.vis;
+        let enum_ident = spec.ident;
+        // Variant docs are the Display messages, so only the enum links back
+        let docs = spec.docs();
+        let derives = spec.derives();
+        let variant_attrs = spec.variant_attrs();
+        let inline_variants = spec.inline_variants(|message| quote!(#[doc = #message]), false);
+        let wrapped_variants = spec.wrapped().into_iter().map(|member| {
+            let cfg = member.cfg_attr();
+            let variant = &member.variant;
+            let ty = &member.ty;
+            let discriminant = member.discriminant();
+            match spec.location_type() {
+                Some(location) => quote! {
+                    #cfg
+                    #variant_attrs
+                    #[doc = "{0} at {1}"]
+                    #variant(#ty, #location) #discriminant
+                },
+                None => quote! {
+                    #cfg
+                    #variant_attrs
+                    #[doc = "{0}"]
+                    #variant(#ty) #discriminant
+                },
+            }
+        });
+        let error_impl = error_impl(self, spec);
+        quote! {
+            #docs
+            #[derive(::displaydoc::Display)]
+            #derives
+            #vis enum #enum_ident {
+                #(#wrapped_variants,)*
+                #(#inline_variants,)*
+            }
+
+            #error_impl
+        }
+    }
+}
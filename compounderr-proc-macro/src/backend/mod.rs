@@ -0,0 +1,294 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Ident, LitStr};
+
+use crate::{ComposeConfig, ErrorMember, InlineVariant};
+
+mod displaydoc;
+mod plain;
+mod snafu;
+mod thiserror;
+
+// Crates the composed enums can be generated for, picked with `backend = "..."`
+#[derive(Clone, Copy, Default, PartialEq)]
+pub(crate) enum BackendKind {
+    #[default]
+    Thiserror,
+    Snafu,
+    Displaydoc,
+    Plain,
+}
+
+impl BackendKind {
+    pub(crate) fn codegen(self) -> &'static dyn Backend {
+        match self {
+            BackendKind::Thiserror => &thiserror::Thiserror,
+            BackendKind::Snafu => &snafu::Snafu,
+            BackendKind::Displaydoc => &displaydoc::Displaydoc,
+            BackendKind::Plain => &plain::Plain,
+        }
+    }
+}
+
+// Emits the composed enum itself, everything else is generated the same for all backends
+pub(crate) trait Backend {
+    // The enum definition, with its Display and Error impls
+    fn enum_def(&self, spec: &EnumSpec) -> TokenStream2;
+
+    // `From` impls of the member types, unless derived along with the enum
+    fn member_conversions(&self, spec: &EnumSpec) -> TokenStream2 {
+        member_from_impls(self, spec)
+    }
+
+    // Fields of a wrapping variant, as pattern or constructor, from the member error
+    // and, with track_caller, the location
+    fn variant_fields(&self, member: TokenStream2, location: Option<TokenStream2>) -> TokenStream2 {
+        match location {
+            Some(location) => quote!((#member, #location)),
+            None => quote!((#member)),
+        }
+    }
+}
+
+// A composed enum to emit
+pub(crate) struct EnumSpec<'a> {
+    pub(crate) ident: &'a Ident,
+    pub(crate) members: &'a [ErrorMember],
+    pub(crate) config: &'a ComposeConfig,
+}
+
+impl EnumSpec<'_> {
+    // The variants, in order
+    pub(crate) fn variants(&self) -> Vec<&Ident> {
+        self.members.iter().map(|member| &member.variant).collect()
+    }
+
+    // The cfg of each variant, gating everything generated for its member
+    pub(crate) fn cfgs(&self) -> Vec<TokenStream2> {
+        self.members.iter().map(ErrorMember::cfg_attr).collect()
+    }
+
+    // The fieldless kind enum, which helpers shared with the opaque struct match on
+    pub(crate) fn kind_ident(&self) -> Ident {
+        crate::name_error_kind(self.ident)
+    }
+
+    // Members wrapping an error type, as opposed to inline variants
+    pub(crate) fn wrapped(&self) -> Vec<&ErrorMember> {
+        self.members
+            .iter()
+            .filter(|member| member.inline.is_none())
+            .collect()
+    }
+
+    // One wrapped member per type, a type routed to several variants counting once, as
+    // the member taking the rest
+    pub(crate) fn converted(&self) -> Vec<&ErrorMember> {
+        self.wrapped()
+            .into_iter()
+            .filter(|member| !member.is_routed() && !member.shared)
+            .collect()
+    }
+
+    // Brings `ComposedError` in scope for the helpers calling its methods, when the enum
+    // takes them from it rather than having its own
+    pub(crate) fn composed_trait_in_scope(&self) -> TokenStream2 {
+        match &self.config.composed_trait {
+            Some(module) => quote!(use #module::ComposedError as _;),
+            None => TokenStream2::new(),
+        }
+    }
+
+    // Derives besides the backend's own, their attributes, and the repr.
+    // Debug is derived too, unless written by hand for compact_debug.
+    fn derives(&self) -> TokenStream2 {
+        let debug = (!self.config.compact_debug).then(|| quote!(#[derive(Debug)]));
+        let extra_derives = self.config.extra_derives();
+        let user_derives = self.config.user_derives();
+        let serde_attrs = self.config.serde_attrs();
+        let uniffi = self.config.uniffi_attrs();
+        let repr = self.config.repr_attr();
+        quote!(#debug #extra_derives #user_derives #serde_attrs #uniffi #repr)
+    }
+
+    // Links back to the function the enum is composed for
+    pub(crate) fn docs(&self) -> TokenStream2 {
+        match &self.config.origin {
+            Some(origin) => {
+                let doc = format!("Errors of [`{}`].", crate::doc_path(origin));
+                quote!(#[doc = #doc])
+            }
+            None => TokenStream2::new(),
+        }
+    }
+
+    // Links from a wrapping variant to its member type
+    fn variant_docs(&self, member: &ErrorMember) -> TokenStream2 {
+        let doc = match (&member.dyn_trait, &member.ty) {
+            (Some(dyn_trait), _) => format!(
+                "Holds any error implementing [`{}`].",
+                crate::doc_path(dyn_trait)
+            ),
+            (None, syn::Type::Path(ty)) if ty.qself.is_none() => {
+                format!("Wraps [`{}`].", crate::doc_path(&ty.path))
+            }
+            (None, _) => return TokenStream2::new(),
+        };
+        quote!(#[doc = #doc])
+    }
+
+    // Sealed variants can be matched, but not constructed, outside the crate
+    fn variant_attrs(&self) -> TokenStream2 {
+        if self.config.sealed {
+            quote!(#[non_exhaustive])
+        } else {
+            TokenStream2::new()
+        }
+    }
+
+    // The type of the location field, with track_caller
+    fn location_type(&self) -> Option<TokenStream2> {
+        self.config
+            .track_caller
+            .then(|| quote!(&'static ::core::panic::Location<'static>))
+    }
+
+    // The inline variants, each with the backend's attribute carrying the message.
+    // With `braced`, variants without fields are still written as structs.
+    fn inline_variants(
+        &self,
+        message_attr: impl Fn(&LitStr) -> TokenStream2,
+        braced: bool,
+    ) -> Vec<TokenStream2> {
+        let variant_attrs = self.variant_attrs();
+        self.members
+            .iter()
+            .filter_map(|member| {
+                let InlineVariant { fields, message } = member.inline.as_ref()?;
+                let cfg = member.cfg_attr();
+                let variant = &member.variant;
+                let discriminant = member.discriminant();
+                let message_attr = message_attr(message);
+                let fields = match fields {
+                    Some(fields) => quote!(#fields),
+                    None if braced => quote!({}),
+                    None => TokenStream2::new(),
+                };
+                Some(quote! {
+                    #cfg
+                    #variant_attrs
+                    #message_attr
+                    #variant #fields #discriminant
+                })
+            })
+            .collect()
+    }
+}
+
+// `From` impls wrapping each member type, recording the location with track_caller
+fn member_from_impls(backend: &(impl Backend + ?Sized), spec: &EnumSpec) -> TokenStream2 {
+    let members = spec
+        .wrapped()
+        .into_iter()
+        .filter(|member| member.route.is_none() && !member.shared)
+        .collect();
+    let impls = wrapping_from_impls(backend, spec, members);
+    let routed_from = routed_from_impls(backend, spec);
+    quote!(#impls #routed_from)
+}
+
+// `From` impls of the given members, passing the error through the member's
+// `map = fn` first, if any
+fn wrapping_from_impls(
+    backend: &(impl Backend + ?Sized),
+    spec: &EnumSpec,
+    members: Vec<&ErrorMember>,
+) -> TokenStream2 {
+    let enum_ident = spec.ident;
+    let track_caller = spec.config.track_caller.then(|| quote!(#[track_caller]));
+    let location = spec
+        .config
+        .track_caller
+        .then(|| quote!(::core::panic::Location::caller()));
+    let impls = members.into_iter().map(|member| {
+        let cfg = member.cfg_attr();
+        let variant = &member.variant;
+        let ty = &member.ty;
+        let source = match &member.mapper {
+            Some(mapper) => quote!(#mapper(source)),
+            None => quote!(source),
+        };
+        let fields = backend.variant_fields(source, location.clone());
+        let value = spec.config.reported(quote!(#enum_ident::#variant #fields));
+        quote! {
+            #cfg
+            impl From<#ty> for #enum_ident {
+                #track_caller
+                fn from(source: #ty) -> Self {
+                    #value
+                }
+            }
+        }
+    });
+    quote!(#(#impls)*)
+}
+
+// `From` impls of the types routed to several variants, into the variant matching the error
+fn routed_from_impls(backend: &(impl Backend + ?Sized), spec: &EnumSpec) -> TokenStream2 {
+    let enum_ident = spec.ident;
+    let track_caller = spec.config.track_caller.then(|| quote!(#[track_caller]));
+    let location = spec
+        .config
+        .track_caller
+        .then(|| quote!(::core::panic::Location::caller()));
+    let fields = backend.variant_fields(quote!(source), location);
+    crate::routed_from_impls(enum_ident, spec.members, quote!(#track_caller), |variant| {
+        spec.config.reported(quote!(#enum_ident::#variant #fields))
+    })
+}
+
+// `std::error::Error` for backends that only derive Display. The source is forwarded
+// as thiserror's transparent would, or with track_caller is the member itself.
+// Inline variants report a field named `source`.
+fn error_impl(backend: &(impl Backend + ?Sized), spec: &EnumSpec) -> TokenStream2 {
+    let enum_ident = spec.ident;
+    let ignored_location = spec.config.track_caller.then(|| quote!(_));
+    let arms = spec.wrapped().into_iter().map(|member| {
+        let cfg = member.cfg_attr();
+        let variant = &member.variant;
+        let fields = backend.variant_fields(quote!(e), ignored_location.clone());
+        let source = if member.dyn_trait.is_some() {
+            // Forwarded through the trait object, which can't be cast to dyn Error
+            quote!(::core::error::Error::source(&**e))
+        } else if spec.config.track_caller {
+            quote!(Some(e as &(dyn ::core::error::Error + 'static)))
+        } else {
+            quote!(::core::error::Error::source(e))
+        };
+        quote!(#cfg #enum_ident::#variant #fields => #source,)
+    });
+    let inline_arms = spec.members.iter().filter_map(|member| {
+        let fields = member.inline.as_ref()?.fields.as_ref()?;
+        fields
+            .named
+            .iter()
+            .any(|field| field.ident.as_ref().is_some_and(|ident| ident == "source"))
+            .then(|| {
+                let cfg = member.cfg_attr();
+                let variant = &member.variant;
+                quote!(#cfg #enum_ident::#variant { source, .. } => Some(source),)
+            })
+    });
+    quote! {
+        impl ::core::error::Error for #enum_ident {
+            fn source(&self) -> Option<&(dyn ::core::error::Error + 'static)> {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #(#arms)*
+                    #(#inline_arms)*
+                    _ => None,
+                }
+            }
+        }
+    }
+}
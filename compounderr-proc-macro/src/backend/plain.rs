@@ -0,0 +1,75 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use super::{error_impl, Backend, EnumSpec};
+
+// Implements Display and Error by hand, without depending on an error crate
+pub(crate) struct Plain;
+
+impl Backend for Plain {
+    fn enum_def(&self, spec: &EnumSpec) -> TokenStream2 {
+        let vis = &spec.config.vis;
+        let enum_ident = spec.ident;
+        let docs = spec.docs();
+        let derives = spec.derives();
+        let variant_attrs = spec.variant_attrs();
+        let inline_variants = spec.inline_variants(|_| TokenStream2::new(), false);
+        let wrapped = spec.wrapped();
+        let wrapped_variants = wrapped.iter().map(|member| {
+            let cfg = member.cfg_attr();
+            let variant = &member.variant;
+            let ty = &member.ty;
+            let discriminant = member.discriminant();
+            let variant_docs = spec.variant_docs(member);
+            match spec.location_type() {
+                Some(location) => {
+                    quote!(#cfg #variant_docs #variant_attrs #variant(#ty, #location) #discriminant)
+                }
+                None => quote!(#cfg #variant_docs #variant_attrs #variant(#ty) #discriminant),
+            }
+        });
+        let wrapped_arms = wrapped.iter().map(|member| {
+            let cfg = member.cfg_attr();
+            let variant = &member.variant;
+            if spec.config.track_caller {
+                quote!(#cfg #enum_ident::#variant(e, location) => write!(f, "{} at {}", e, location),)
+            } else {
+                quote!(#cfg #enum_ident::#variant(e) => ::core::fmt::Display::fmt(e, f),)
+            }
+        });
+        // Inline messages name the fields, bound here for the format string to capture
+        let inline_arms = spec.members.iter().filter_map(|member| {
+            let inline = member.inline.as_ref()?;
+            let cfg = member.cfg_attr();
+            let variant = &member.variant;
+            let message = &inline.message;
+            let names = inline
+                .fields
+                .iter()
+                .flat_map(|fields| fields.named.iter())
+                .map(|field| &field.ident);
+            Some(quote!(#cfg #enum_ident::#variant { #(#names,)* } => write!(f, #message),))
+        });
+        let error_impl = error_impl(self, spec);
+        quote! {
+            #docs
+            #derives
+            #vis enum #enum_ident {
+                #(#wrapped_variants,)*
+                #(#inline_variants,)*
+            }
+
+            impl ::core::fmt::Display for #enum_ident {
+                #[allow(unused_variables)]
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    match self {
+                        #(#wrapped_arms)*
+                        #(#inline_arms)*
+                    }
+                }
+            }
+
+            #error_impl
+        }
+    }
+}
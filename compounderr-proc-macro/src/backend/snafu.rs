@@ -0,0 +1,59 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use super::{Backend, EnumSpec};
+
+// Derives `snafu::Snafu`. The member error is the variant's `source`, so snafu generates
+// a context selector per variant. The From impls keep `?` working without `.context()`.
+pub(crate) struct Snafu;
+
+impl Backend for Snafu {
+    fn enum_def(&self, spec: &EnumSpec) -> TokenStream2 {
+        let vis = &spec.config.vis;
+        let enum_ident = spec.ident;
+        let docs = spec.docs();
+        let derives = spec.derives();
+        let variant_attrs = spec.variant_attrs();
+        // Snafu wants struct variants, to generate their context selectors
+        let inline_variants =
+            spec.inline_variants(|message| quote!(#[snafu(display(#message))]), true);
+        let wrapped_variants = spec.wrapped().into_iter().map(|member| {
+            let cfg = member.cfg_attr();
+            let variant = &member.variant;
+            let ty = &member.ty;
+            let discriminant = member.discriminant();
+            let variant_docs = spec.variant_docs(member);
+            // snafu can't take the source of a boxed trait object
+            let source_attr = member
+                .dyn_trait
+                .is_some()
+                .then(|| quote!(#[snafu(source(false))]));
+            quote! {
+                #cfg
+                #variant_docs
+                #variant_attrs
+                #[snafu(display("{source}"))]
+                #variant { #source_attr source: #ty } #discriminant
+            }
+        });
+        quote! {
+            #docs
+            #[derive(::snafu::Snafu)]
+            #[snafu(visibility(#vis))]
+            #derives
+            #vis enum #enum_ident {
+                #(#wrapped_variants,)*
+                #(#inline_variants,)*
+            }
+        }
+    }
+
+    // Snafu has its own implicit locations, track_caller is rejected with it
+    fn variant_fields(
+        &self,
+        member: TokenStream2,
+        _location: Option<TokenStream2>,
+    ) -> TokenStream2 {
+        quote!({ source: #member })
+    }
+}
@@ -0,0 +1,91 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use super::{member_from_impls, routed_from_impls, wrapping_from_impls, Backend, EnumSpec};
+
+// Derives `thiserror::Error`, members being transparent `#[from]` variants
+pub(crate) struct Thiserror;
+
+impl Backend for Thiserror {
+    fn enum_def(&self, spec: &EnumSpec) -> TokenStream2 {
+        let crate_path = &spec.config.crate_path;
+        let vis = &spec.config.vis;
+        let enum_ident = spec.ident;
+        let docs = spec.docs();
+        let derives = spec.derives();
+        let variant_attrs = spec.variant_attrs();
+        let inline_variants = spec.inline_variants(|message| quote!(#[error(#message)]), false);
+        let wrapped_variants = spec.wrapped().into_iter().map(|member| {
+            let cfg = member.cfg_attr();
+            let variant = &member.variant;
+            let ty = &member.ty;
+            let discriminant = member.discriminant();
+            let variant_docs = spec.variant_docs(member);
+            // A type routed to several variants gets a From impl matching on the error,
+            // and one mapped first a From impl calling the mapper. Shared ones get none,
+            // and with a report_hook all are written out to call it.
+            let from = (member.route.is_none()
+                && member.mapper.is_none()
+                && !member.shared
+                && spec.config.report_hook.is_none())
+            .then(|| quote!(#[from]));
+            match spec.location_type() {
+                // thiserror can't take the source of a boxed trait object
+                Some(location) if member.dyn_trait.is_some() => quote! {
+                    #cfg
+                    #variant_docs
+                    #variant_attrs
+                    #[error("{0} at {1}")]
+                    #variant(#ty, #location) #discriminant
+                },
+                Some(location) => quote! {
+                    #cfg
+                    #variant_docs
+                    #variant_attrs
+                    #[error("{0} at {1}")]
+                    #variant(#[source] #ty, #location) #discriminant
+                },
+                None if member.dyn_trait.is_some() => quote! {
+                    #cfg
+                    #variant_docs
+                    #variant_attrs
+                    #[error("{0}")]
+                    #variant(#ty) #discriminant
+                },
+                None => quote! {
+                    #cfg
+                    #variant_docs
+                    #variant_attrs
+                    #[error(transparent)]
+                    #variant(#from #ty) #discriminant
+                },
+            }
+        });
+        quote! {
+            #docs
+            #[derive(#crate_path::Error)]
+            #derives
+            #vis enum #enum_ident {
+                #(#wrapped_variants,)*
+                #(#inline_variants,)*
+            }
+        }
+    }
+
+    // thiserror derives them with #[from], except to capture locations, report the error,
+    // map it, or box a trait object
+    fn member_conversions(&self, spec: &EnumSpec) -> TokenStream2 {
+        if spec.config.track_caller || spec.config.report_hook.is_some() {
+            member_from_impls(self, spec)
+        } else {
+            let mapped = spec
+                .wrapped()
+                .into_iter()
+                .filter(|member| member.mapper.is_some() || member.dyn_trait.is_some())
+                .collect();
+            let mapped_from = wrapping_from_impls(self, spec, mapped);
+            let routed_from = routed_from_impls(self, spec);
+            quote!(#mapped_from #routed_from)
+        }
+    }
+}
@@ -0,0 +1,345 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote,
+    visit_mut::{self, VisitMut},
+    Block, Expr, ExprClosure, Ident, Item, Local, Pat, ReturnType, Stmt, Type,
+};
+
+use crate::{
+    compose_error_enum, dedupe_members, gate_items, is_errorset_attr, process_bare_function,
+    replace_func_output, ComposeConfig, ErrorMember,
+};
+
+// Rewrites inside function bodies, behind the `body-analysis` feature. Signatures are
+// rewritten in the crate root, these only walk the statements and expressions.
+
+// Composes the errors of nested functions and closures in the bodies of the item.
+// Those are local to the block, so never placed in the module or sealed.
+pub(crate) fn compose_nested(item: &mut Item, config: &ComposeConfig) {
+    let nested_config = ComposeConfig {
+        module: None,
+        sealed: false,
+        ..config.clone()
+    };
+    NestedComposer {
+        config: &nested_config,
+    }
+    .visit_item_mut(item);
+}
+
+// Calls `.ctx(..)` and `.with_ctx(..)` through the context trait of the function's enum
+pub(crate) fn call_context_trait(body: &mut Block, context_trait: syn::Path) {
+    ContextCalls { context_trait }.visit_block_mut(body);
+}
+
+// Rewrites each `expr?` of the body to `expr.map_err(<Composed>::from)?`
+pub(crate) fn convert_question_marks(body: &mut Block, composed_error_type: &Type) {
+    let composed_error_type = composed_error_type.clone();
+    QuestionConversions {
+        composed_error_type,
+    }
+    .visit_block_mut(body);
+}
+
+// Walks function bodies, composing errors for nested `#[errorset]` functions
+// and `errorset_closure!` closures. The generated enums are placed as items
+// at the top of the enclosing block, so they are scoped to it.
+struct NestedComposer<'a> {
+    config: &'a ComposeConfig,
+}
+
+impl VisitMut for NestedComposer<'_> {
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        let mut enums = Vec::new();
+        for stmt in &mut block.stmts {
+            match stmt {
+                Stmt::Item(Item::Fn(function)) if function.attrs.iter().any(is_errorset_attr) => {
+                    let (scope_name, functions) = process_bare_function(function, self.config);
+                    enums.extend(
+                        functions
+                            .iter()
+                            .filter_map(|func| func.diagnostics.as_ref())
+                            .map(syn::Error::to_compile_error),
+                    );
+                    enums.extend(functions.iter().map(|func| {
+                        let enum_ident = self.config.name_enum(func, &scope_name);
+                        gate_items(
+                            compose_error_enum(
+                                &enum_ident,
+                                &func.error_set,
+                                &self.config.for_function(func),
+                            ),
+                            &func.cfgs,
+                        )
+                    }));
+                }
+                Stmt::Local(local) => enums.extend(process_closure_local(local, self.config)),
+                _ => {}
+            }
+        }
+        for composed_enum in enums {
+            block
+                .stmts
+                .insert(0, Stmt::Item(Item::Verbatim(composed_enum)));
+        }
+        // Continue into (possibly nested) blocks
+        visit_mut::visit_block_mut(self, block);
+    }
+}
+
+// Calls `.ctx(..)` and `.with_ctx(..)` in a function body through its own context trait,
+// as the errors also convert into the enums of other functions in the module
+struct ContextCalls {
+    context_trait: syn::Path,
+}
+
+impl VisitMut for ContextCalls {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        visit_mut::visit_expr_mut(self, expr);
+        let Expr::MethodCall(call) = expr else {
+            return;
+        };
+        if call.method != "ctx" && call.method != "with_ctx" {
+            return;
+        }
+        let context_trait = &self.context_trait;
+        let method = &call.method;
+        let turbofish = &call.turbofish;
+        let receiver = &call.receiver;
+        let args = call.args.iter();
+        *expr = parse_quote!(#context_trait::#method #turbofish(#receiver, #(#args),*));
+    }
+
+    // Nested functions have errorsets of their own
+    fn visit_item_mut(&mut self, _item: &mut Item) {}
+}
+
+// Converts the error of each `expr?` in a function body explicitly, with `map_err`,
+// for when inference can't pick the From impl, as with generic combinators. Types aren't
+// known here, so every `?` is rewritten, calling the From impl it would have called anyway.
+struct QuestionConversions {
+    composed_error_type: Type,
+}
+
+impl VisitMut for QuestionConversions {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        match expr {
+            // `?` in a closure or async block leaves that, not the function
+            Expr::Closure(_) | Expr::Async(_) => return,
+            Expr::Try(try_expr) => {
+                let inner = &try_expr.expr;
+                let composed_error_type = &self.composed_error_type;
+                *try_expr.expr = parse_quote!(#inner.map_err(<#composed_error_type>::from));
+            }
+            _ => {}
+        }
+        visit_mut::visit_expr_mut(self, expr);
+    }
+
+    // Nested functions have errorsets of their own
+    fn visit_item_mut(&mut self, _item: &mut Item) {}
+}
+
+// Input of the `errorset_closure!` helper: `ErrA, ErrB => |args| -> Result<T, _> { .. }`
+struct ClosureErrorset {
+    error_set: Vec<Ident>,
+    closure: ExprClosure,
+}
+
+impl Parse for ClosureErrorset {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut error_set = Vec::new();
+        while !input.peek(syn::Token![=>]) {
+            error_set.push(input.parse()?);
+            if !input.peek(syn::Token![=>]) {
+                input.parse::<syn::Token![,]>()?;
+            }
+        }
+        input.parse::<syn::Token![=>]>()?;
+        let closure = input.parse()?;
+        Ok(ClosureErrorset { error_set, closure })
+    }
+}
+
+// Mutates a `let name = errorset_closure!(..);` statement in place, replacing the
+// macro with the closure itself. Returns the composed enum for the closure, if any.
+fn process_closure_local(local: &mut Local, config: &ComposeConfig) -> Option<TokenStream2> {
+    let Pat::Ident(binding) = &local.pat else {
+        return None;
+    };
+    let init = local.init.as_mut()?;
+    let Expr::Macro(expr_macro) = &*init.expr else {
+        return None;
+    };
+    if expr_macro.mac.path.segments.last().unwrap().ident != "errorset_closure" {
+        return None;
+    }
+    let ClosureErrorset {
+        error_set,
+        mut closure,
+    } = match expr_macro.mac.parse_body() {
+        Ok(parsed) => parsed,
+        // In place of the macro, which would otherwise report being misplaced
+        Err(error) => {
+            *init.expr = Expr::Verbatim(error.to_compile_error());
+            return None;
+        }
+    };
+
+    let enum_ident = config.name_enum_for(&binding.ident, "", None);
+    replace_func_output(&mut closure.output, &config.enum_type(&enum_ident), false);
+    *init.expr = Expr::Closure(closure);
+    let mut error_set: Vec<ErrorMember> = error_set.into_iter().map(ErrorMember::from).collect();
+    let duplicates = dedupe_members(&mut error_set).map(|error| error.to_compile_error());
+    let composed_enum = compose_error_enum(&enum_ident, &error_set, config);
+    Some(quote!(#duplicates #composed_enum))
+}
+
+// `-> T` becomes `-> Result<T, _>`, and no return type `-> Result<(), _>`
+pub(crate) fn wrap_throws_output(output: &mut ReturnType) {
+    *output = match &*output {
+        ReturnType::Default => parse_quote!(-> Result<(), _>),
+        ReturnType::Type(arrow, ty) => parse_quote!(#arrow Result<#ty, _>),
+    };
+}
+
+// Wraps the values a `throws` function returns in `Ok`, the tail expression and
+// `return` expressions. `Err(..)` is left as written, and so is a body that ends
+// in a `return` statement.
+pub(crate) fn wrap_throws_body(body: &mut Block) {
+    ThrowsReturns.visit_block_mut(body);
+    match body.stmts.last_mut() {
+        Some(Stmt::Expr(tail, None)) => {
+            if !is_err_call(tail) {
+                *tail = parse_quote!(::core::result::Result::Ok(#tail));
+            }
+        }
+        Some(Stmt::Expr(Expr::Return(_), Some(_))) => {}
+        _ => body.stmts.push(Stmt::Expr(
+            parse_quote!(::core::result::Result::Ok(())),
+            None,
+        )),
+    }
+}
+
+// Whether the expression is a call to `Err(..)`
+fn is_err_call(expr: &Expr) -> bool {
+    match expr {
+        Expr::Call(call) => {
+            matches!(&*call.func, Expr::Path(func) if func.path.segments.last().unwrap().ident == "Err")
+        }
+        _ => false,
+    }
+}
+
+// Converts the members a body returns as `Err(Member ..)` into the composed error, in
+// `return` expressions and the tail, through its branches. A member is recognized by its
+// type name heading the value: `Missing`, `Missing(..)`, `Missing { .. }`, `Missing::new(..)`.
+pub(crate) fn convert_returned_members(body: &mut Block, members: Vec<Ident>) {
+    let mut conversions = MemberReturns { members };
+    conversions.visit_block_mut(body);
+    if let Some(Stmt::Expr(tail, None)) = body.stmts.last_mut() {
+        conversions.convert_tail(tail);
+    }
+}
+
+struct MemberReturns {
+    members: Vec<Ident>,
+}
+
+impl MemberReturns {
+    fn convert_tail(&self, expr: &mut Expr) {
+        match expr {
+            Expr::If(if_expr) => {
+                self.convert_block_tail(&mut if_expr.then_branch);
+                if let Some((_, else_branch)) = &mut if_expr.else_branch {
+                    self.convert_tail(else_branch);
+                }
+            }
+            Expr::Match(match_expr) => {
+                for arm in &mut match_expr.arms {
+                    self.convert_tail(&mut arm.body);
+                }
+            }
+            Expr::Block(block) => self.convert_block_tail(&mut block.block),
+            expr => self.convert(expr),
+        }
+    }
+
+    fn convert_block_tail(&self, block: &mut Block) {
+        if let Some(Stmt::Expr(tail, None)) = block.stmts.last_mut() {
+            self.convert_tail(tail);
+        }
+    }
+
+    // `Err(Member)` becomes `Err(Into::into(Member))`
+    fn convert(&self, expr: &mut Expr) {
+        if !is_err_call(expr) {
+            return;
+        }
+        let Expr::Call(call) = expr else {
+            return;
+        };
+        let Some(value) = call.args.first_mut() else {
+            return;
+        };
+        let head = match &*value {
+            Expr::Path(path) => &path.path,
+            Expr::Struct(literal) => &literal.path,
+            Expr::Call(constructor) => match &*constructor.func {
+                Expr::Path(path) => &path.path,
+                _ => return,
+            },
+            _ => return,
+        };
+        if head.leading_colon.is_none()
+            && head.segments.len() <= 2
+            && self.members.contains(&head.segments[0].ident)
+        {
+            *value = parse_quote!(::core::convert::Into::into(#value));
+        }
+    }
+}
+
+impl VisitMut for MemberReturns {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        match expr {
+            // Returning from a closure or async block, not the function
+            Expr::Closure(_) | Expr::Async(_) => return,
+            Expr::Return(ret) => {
+                if let Some(value) = &mut ret.expr {
+                    self.convert(value);
+                }
+            }
+            _ => {}
+        }
+        visit_mut::visit_expr_mut(self, expr);
+    }
+
+    // Nested functions return for themselves
+    fn visit_item_mut(&mut self, _item: &mut Item) {}
+}
+
+// Wraps the `return` values of a `throws` function body in `Ok`, unless already `Err(..)`
+struct ThrowsReturns;
+
+impl VisitMut for ThrowsReturns {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        match expr {
+            // Returning from a closure or async block, not the function
+            Expr::Closure(_) | Expr::Async(_) => return,
+            Expr::Return(ret) => match &mut ret.expr {
+                Some(value) if is_err_call(value) => {}
+                Some(value) => **value = parse_quote!(::core::result::Result::Ok(#value)),
+                None => ret.expr = Some(parse_quote!(::core::result::Result::Ok(()))),
+            },
+            _ => {}
+        }
+        visit_mut::visit_expr_mut(self, expr);
+    }
+
+    // Nested functions return for themselves
+    fn visit_item_mut(&mut self, _item: &mut Item) {}
+}
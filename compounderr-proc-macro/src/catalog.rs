@@ -0,0 +1,174 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote, ToTokens};
+
+use crate::{
+    name_error_kind, name_result_alias, pascal_to_snake, ComposeConfig, FuncErrors, Severity,
+};
+
+// A module per scope, `foo_impl_errors` for `impl Foo`, re-exporting the composed enums
+// of the scope with their kinds and result aliases, so they can be glob imported together
+pub(crate) fn compose_scope_index(
+    scope_name: &str,
+    functions: &[FuncErrors],
+    config: &ComposeConfig,
+) -> TokenStream2 {
+    let vis = &config.vis;
+    let module = match functions.first() {
+        // Bare functions are their own scope
+        Some(func) if scope_name.is_empty() => format_ident!("{}_errors", func.name),
+        _ => format_ident!("{}_errors", pascal_to_snake(scope_name)),
+    };
+    let reexports = functions.iter().map(|func| {
+        let config = config.for_function(func);
+        let enum_ident = config.name_enum(func, scope_name);
+        let mut items = vec![enum_ident.clone()];
+        if !config.minimal && !func.newtype {
+            items.push(name_error_kind(&enum_ident));
+        }
+        if config.result_alias {
+            items.push(name_result_alias(&enum_ident));
+        }
+        let (vis, cfgs) = (&config.vis, &func.cfgs);
+        quote! {
+            #(#cfgs)*
+            #vis use super::{#(#items),*};
+        }
+    });
+    quote! {
+        /// The composed errors of the scope, to import with a glob.
+        // Within the crate, only some of them may be imported
+        #[allow(unused_imports)]
+        #vis mod #module {
+            #(#reexports)*
+        }
+    }
+}
+
+// A module per scope, `foo_impl_catalog` for `impl Foo`, with an `ERROR_CATALOG` describing
+// its enums and their variants, for generating error reference docs. Codes are those of
+// `code()`, the kind as u16.
+pub(crate) fn compose_error_catalog(
+    scope_name: &str,
+    functions: &[FuncErrors],
+    config: &ComposeConfig,
+) -> TokenStream2 {
+    let vis = &config.vis;
+    let module = match functions.first() {
+        // Bare functions are their own scope
+        Some(func) if scope_name.is_empty() => format_ident!("{}_catalog", func.name),
+        _ => format_ident!("{}_catalog", pascal_to_snake(scope_name)),
+    };
+    // Newtypes have no kinds to describe
+    let errors = functions.iter().filter(|func| !func.newtype).map(|func| {
+        let fn_cfgs = &func.cfgs;
+        let function = func.name.to_string();
+        let enum_ident = config.name_enum(func, scope_name);
+        let kind_ident = name_error_kind(&enum_ident);
+        let variants = func.error_set.iter().map(|member| {
+            let cfg = member.cfg_attr();
+            let variant = &member.variant;
+            let name = variant.to_string();
+            let optional = |value: Option<TokenStream2>| match value {
+                Some(value) => quote!(Some(#value)),
+                None => quote!(None),
+            };
+            let (member_type, message) = match &member.inline {
+                Some(inline) => (None, Some(inline.message.to_token_stream())),
+                None => {
+                    let ty = &member.ty;
+                    let ty = quote!(#ty)
+                        .to_string()
+                        .replace(" :: ", "::")
+                        .replace(":: ", "::")
+                        .replace(" <", "<")
+                        .replace("< ", "<")
+                        .replace(" >", ">");
+                    (Some(quote!(#ty)), None)
+                }
+            };
+            let member_type = optional(member_type);
+            let message = optional(message);
+            let retryable = member.retryable;
+            let severity = match member.severity {
+                Severity::Warning => "Warning",
+                Severity::Error => "Error",
+                Severity::Fatal => "Fatal",
+            };
+            let exit_code = optional(member.exit_code.as_ref().map(ToTokens::to_token_stream));
+            let hint = optional(member.hint.as_ref().map(ToTokens::to_token_stream));
+            quote! {
+                #cfg
+                VariantMeta {
+                    name: #name,
+                    code: super::#kind_ident::#variant as u16,
+                    member: #member_type,
+                    message: #message,
+                    retryable: #retryable,
+                    severity: #severity,
+                    exit_code: #exit_code,
+                    hint: #hint,
+                }
+            }
+        });
+        let name = enum_ident.to_string();
+        quote! {
+            #(#fn_cfgs)*
+            ErrorMeta {
+                name: #name,
+                function: #function,
+                variants: &[#(#variants),*],
+            }
+        }
+    });
+    // Each catalog has its own types, unless shared ones are declared
+    let types = match &config.catalog_types {
+        Some(path) => quote!(use #path::{ErrorMeta, VariantMeta};),
+        None => catalog_types(),
+    };
+    quote! {
+        /// Catalog of the composed errors of the scope, for error reference docs.
+        #vis mod #module {
+            #types
+
+            /// All the composed errors of the scope.
+            pub const ERROR_CATALOG: &[ErrorMeta] = &[#(#errors),*];
+        }
+    }
+}
+
+// The item types of error catalogs
+pub(crate) fn catalog_types() -> TokenStream2 {
+    quote! {
+        /// A composed error enum.
+        #[derive(Debug, Clone, Copy)]
+        pub struct ErrorMeta {
+            /// Name of the enum.
+            pub name: &'static str,
+            /// Name of the function returning it.
+            pub function: &'static str,
+            /// Its variants, in declaration order.
+            pub variants: &'static [VariantMeta],
+        }
+
+        /// A variant of a composed error enum.
+        #[derive(Debug, Clone, Copy)]
+        pub struct VariantMeta {
+            /// Name of the variant.
+            pub name: &'static str,
+            /// Numeric code of the variant, its kind as u16.
+            pub code: u16,
+            /// The member error type wrapped, unless an inline variant.
+            pub member: Option<&'static str>,
+            /// The message of an inline variant, members display their own.
+            pub message: Option<&'static str>,
+            /// Annotated `retryable`.
+            pub retryable: bool,
+            /// `Warning`, `Error` or `Fatal`.
+            pub severity: &'static str,
+            /// Annotated `exit = N`.
+            pub exit_code: Option<u8>,
+            /// Annotated `hint = "..."`.
+            pub hint: Option<&'static str>,
+        }
+    }
+}
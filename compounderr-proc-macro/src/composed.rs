@@ -0,0 +1,836 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned, ToTokens};
+use syn::{Attribute, Ident, Type};
+
+use crate::{
+    compose_opaque_error, default_impl, features, gate_items, mark_shared_members, name_error_kind,
+    name_result_alias, name_scope_marker, name_sealed_marker, name_sealed_module,
+    shared_constructors, variant_constructors, ComposeConfig, EnumSpec, ErrorMember, FuncErrors,
+    ParentConversion, ViaConversion,
+};
+
+// The composed enum of an errorset, its kind, and the impls shared by every backend
+pub(crate) fn compose_error_enum(
+    enum_ident: &Ident,
+    error_set: &[ErrorMember],
+    config: &ComposeConfig,
+) -> TokenStream2 {
+    // The first variant wrapping a type is its default, taking the From impl
+    let mut members = error_set.to_vec();
+    mark_shared_members(&mut members);
+    // Variants follow the errorset as written, unless sorted by name
+    if config.sorted {
+        members.sort_by_key(|member| member.variant.to_string());
+    }
+    let error_set = &members[..];
+    let vis = &config.vis;
+    let backend = config.backend.codegen();
+    let spec = EnumSpec {
+        ident: enum_ident,
+        members: error_set,
+        config,
+    };
+    let enum_def = backend.enum_def(&spec);
+    let from_impls = backend.member_conversions(&spec);
+    let try_from_impls = try_from_impls(&spec);
+    let result_alias = result_alias(&spec);
+    let widen = features::widen(&spec);
+    let terrors_conversions = features::terrors_conversions(&spec);
+    let (enum_variant_names, kind_variant_names) = features::variant_names(&spec);
+
+    // With `opaque(..)`, the enum itself only exists while its predicate doesn't hold
+    let (debug_gates, opaque) = match config.opaque_gates() {
+        Some((full_gate, opaque_gate)) => {
+            let opaque = compose_opaque_error(enum_ident, error_set, config);
+            (vec![full_gate], gate_items(opaque, &[opaque_gate]))
+        }
+        None => (Vec::new(), TokenStream2::new()),
+    };
+    let code = code(&spec);
+    let (distinct_assertions, copy_assertions) = member_assertions(&spec);
+    // Shared members have no From impl of their own, so they are built by name
+    let construct = |member: &ErrorMember| {
+        let variant = &member.variant;
+        if let Some(inline) = &member.inline {
+            let names = inline.field_names();
+            return quote!(#enum_ident::#variant { #(#names),* });
+        }
+        let location = config
+            .track_caller
+            .then(|| quote!(::core::panic::Location::caller()));
+        let source = match &member.mapper {
+            Some(mapper) => quote!(#mapper(source)),
+            None => quote!(source),
+        };
+        let fields = backend.variant_fields(source, location);
+        quote!(#enum_ident::#variant #fields)
+    };
+    let shared_constructors = shared_constructors(enum_ident, error_set, config, construct);
+    let variant_constructors = variant_constructors(enum_ident, error_set, config, construct);
+    let default_impl = default_impl(enum_ident, error_set, config, construct);
+    let compact_debug = features::compact_debug(&spec);
+    let visitor = features::visitor(&spec);
+    let match_macro = features::match_macro(&spec);
+    let kind_eq = features::kind_eq(&spec);
+    let accessors = accessors(&spec);
+    let full = quote! {
+        #enum_def
+
+        #kind_eq
+
+        #compact_debug
+
+        #visitor
+
+        #match_macro
+
+        #copy_assertions
+
+        #from_impls
+
+        #enum_variant_names
+
+        #terrors_conversions
+
+        #try_from_impls
+
+        #shared_constructors
+
+        #variant_constructors
+
+        #default_impl
+
+        #accessors
+    };
+    let full = gate_items(full, &debug_gates);
+
+    let via_impls = via_impls(&spec);
+    let parent_impls = config
+        .parents
+        .iter()
+        .map(|parent| compose_parent_conversion(enum_ident, error_set, parent, config));
+    let parent_impls = quote!(#(#parent_impls)*);
+    let infallible_from = infallible_from(&spec);
+    let dyn_constructors = dyn_constructors(&spec);
+    let context_ext = features::context_ext(&spec);
+    let ffi_code = features::ffi_code(&spec);
+    let bail_macros = features::bail_macros(&spec);
+    let cli_reporting = features::cli_reporting(&spec);
+    let report = features::report(&spec);
+    let json = features::json(&spec);
+    let problem = features::problem(&spec);
+    let otel = features::otel(&spec);
+    let graphql_extensions = features::graphql_extensions(&spec);
+    let scope_marker = scope_marker(&spec);
+    let repr_check = repr_check(&spec);
+    let severity_check = features::severity_check(&spec);
+    // Only what constructing the enum and `?` need
+    if config.minimal {
+        let never_impls = never_impls(&spec, quote!(match self {}));
+        let composed = quote! {
+            #repr_check
+
+            #distinct_assertions
+
+            #enum_def
+
+            #compact_debug
+
+            #copy_assertions
+
+            #from_impls
+
+            #shared_constructors
+
+            #default_impl
+
+            #via_impls
+
+            #parent_impls
+
+            #infallible_from
+
+            #never_impls
+
+            #dyn_constructors
+
+            #context_ext
+
+            #scope_marker
+        };
+        return if config.sealed {
+            seal_items(enum_ident, composed, vis)
+        } else {
+            composed
+        };
+    }
+    // The opaque struct holds no enum, but its kind is just as empty
+    let never_impls = never_impls(&spec, quote!(match self.kind() {}));
+    let kind_enum = kind_enum(&spec);
+    let composed = quote! {
+        #repr_check
+
+        #severity_check
+
+        #distinct_assertions
+
+        #full
+
+        #opaque
+
+        #kind_enum
+
+        #result_alias
+
+        #widen
+
+        #kind_variant_names
+
+        #code
+
+        #cli_reporting
+
+        #report
+
+        #json
+
+        #problem
+
+        #otel
+
+        #graphql_extensions
+
+        #ffi_code
+
+        #via_impls
+
+        #parent_impls
+
+        #infallible_from
+
+        #never_impls
+
+        #dyn_constructors
+
+        #context_ext
+
+        #bail_macros
+
+        #scope_marker
+    };
+    if config.sealed {
+        seal_items(enum_ident, composed, vis)
+    } else {
+        composed
+    }
+}
+
+// The fieldless kind enum, with what it knows of each member
+fn kind_enum(spec: &EnumSpec) -> TokenStream2 {
+    let enum_ident = spec.ident;
+    let vis = &spec.config.vis;
+    let extra_derives = spec.config.extra_derives();
+    let kind_derives = &spec.config.kind_derives;
+    let kind_derives = (!kind_derives.is_empty()).then(|| quote!(#[derive(#(#kind_derives),*)]));
+    // Everything generated per member is gated by the member's cfg, if any
+    let variants = spec.variants();
+    let cfgs = spec.cfgs();
+    let kind_ident = spec.kind_ident();
+    // The kinds are numbered the same, so `kind() as u8` is stable too
+    let repr = spec.config.repr_attr();
+    let discriminants = spec.members.iter().map(ErrorMember::discriminant);
+    let retryable = spec.members.iter().map(|member| member.retryable);
+    let (_, kind_severity) = features::severity(spec);
+    quote! {
+        /// Fieldless counterpart of
+        #[doc = concat!("[`", stringify!(#enum_ident), "`],")]
+        /// naming the kind of error held without borrowing it. Ordered as declared.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #extra_derives
+        #kind_derives
+        #repr
+        #vis enum #kind_ident {
+            #(#cfgs #variants #discriminants),*
+        }
+
+        // Shared by the enum and its opaque struct, which delegate here
+        impl #kind_ident {
+            /// Whether errors of this kind may succeed if retried, members are annotated `retryable`.
+            pub fn is_retryable(self) -> bool {
+                match self {
+                    #(#cfgs #kind_ident::#variants => #retryable,)*
+                }
+            }
+
+            #kind_severity
+        }
+    }
+}
+
+// Explicit discriminants number the kind enum, which takes a repr to hold them
+fn repr_check(spec: &EnumSpec) -> TokenStream2 {
+    match spec
+        .members
+        .iter()
+        .find(|member| member.discriminant.is_some())
+    {
+        Some(member) if spec.config.repr.is_none() => syn::Error::new(
+            member.variant.span(),
+            "discriminants need an integer repr, like #[compose_errors(repr = \"u8\")]",
+        )
+        .to_compile_error(),
+        _ => TokenStream2::new(),
+    }
+}
+
+fn result_alias(spec: &EnumSpec) -> TokenStream2 {
+    if !spec.config.result_alias {
+        return TokenStream2::new();
+    }
+    let enum_ident = spec.ident;
+    let vis = &spec.config.vis;
+    let alias_ident = name_result_alias(enum_ident);
+    quote! {
+        #[doc = concat!("Result with a [`", stringify!(#enum_ident), "`] error.")]
+        #vis type #alias_ident<T> = ::core::result::Result<T, #enum_ident>;
+    }
+}
+
+// With `opaque(..)`, the numeric code both the enum and the opaque struct report
+fn code(spec: &EnumSpec) -> TokenStream2 {
+    if spec.config.opaque.is_none() {
+        return TokenStream2::new();
+    }
+    let enum_ident = spec.ident;
+    quote! {
+        impl #enum_ident {
+            /// Numeric code of the kind of error held, the same for the enum and the opaque struct.
+            pub fn code(&self) -> u16 {
+                self.kind() as u16
+            }
+        }
+    }
+}
+
+fn scope_marker(spec: &EnumSpec) -> TokenStream2 {
+    match &spec.config.scope {
+        Some(scope) => {
+            let marker = name_scope_marker(scope);
+            let enum_ident = spec.ident;
+            quote!(impl #marker for #enum_ident {})
+        }
+        None => TokenStream2::new(),
+    }
+}
+
+// `TryFrom` back to each member type, handing the enum back on a different variant
+fn try_from_impls(spec: &EnumSpec) -> TokenStream2 {
+    if spec.config.no_try_from {
+        return TokenStream2::new();
+    }
+    let enum_ident = spec.ident;
+    let wrapped = spec.wrapped();
+    let bind_e = spec.config.member_fields(quote!(e));
+    // A boxed `dyn Error` already has a TryFrom, through std's From for any error
+    let impls = spec
+        .converted()
+        .into_iter()
+        .filter(|member| !member.is_boxed_error())
+        .map(|member| {
+            let cfg = member.cfg_attr();
+            let ty = &member.ty;
+            // Back to a routed or shared type from any of its variants
+            let holding = wrapped.iter().filter(|other| {
+                other.variant == member.variant
+                    || (member.is_fallback() && other.is_routed() && other.same_ty(member))
+                    || (other.shared && other.same_ty(member))
+            });
+            let arms = holding.map(|other| {
+                let cfg = other.cfg_attr();
+                let variant = &other.variant;
+                quote!(#cfg #enum_ident::#variant #bind_e => Ok(e),)
+            });
+            quote! {
+                #cfg
+                impl TryFrom<#enum_ident> for #ty {
+                    // On a different variant, the original error is handed back
+                    type Error = #enum_ident;
+                    fn try_from(value: #enum_ident) -> Result<Self, Self::Error> {
+                        #[allow(unreachable_patterns)]
+                        match value {
+                            #(#arms)*
+                            other => Err(other),
+                        }
+                    }
+                }
+            }
+        });
+    quote!(#(#impls)*)
+}
+
+// Compile time checks on the member types, that they are distinct, and Copy with `copy`
+fn member_assertions(spec: &EnumSpec) -> (TokenStream2, TokenStream2) {
+    let converted = spec.converted();
+    let types: Vec<&Type> = converted.iter().map(|member| &member.ty).collect();
+    let cfgs: Vec<TokenStream2> = converted.iter().map(|member| member.cfg_attr()).collect();
+    // Members that are the same type behind different names (aliases, re-exports) would
+    // get conflicting From impls. This extra conflict points at the members, naming the type.
+    let distinct_assertions = types.iter().zip(&cfgs).map(|(ty, cfg)| {
+        quote_spanned! {syn::spanned::Spanned::span(ty)=>
+            #cfg
+            impl ErrorsetMembersMustBeDistinctTypes for #ty {}
+        }
+    });
+    let distinct_assertions = quote! {
+        const _: () = {
+            trait ErrorsetMembersMustBeDistinctTypes {}
+            #(#distinct_assertions)*
+        };
+    };
+    // Names the member that isn't Copy, rather than failing on the derive
+    let copy_assertions = if spec.config.copy {
+        quote! {
+            #(
+                #cfgs
+                const _: fn() = || {
+                    fn assert_copy<T: ::core::marker::Copy>() {}
+                    assert_copy::<#types>();
+                };
+            )*
+        }
+    } else {
+        TokenStream2::new()
+    };
+    (distinct_assertions, copy_assertions)
+}
+
+// The inherent methods of the enum, the kind, classification and access to the member held.
+// With `composed_trait`, access comes from the shared trait's default methods instead, the
+// enum only telling it which member it holds.
+fn accessors(spec: &EnumSpec) -> TokenStream2 {
+    let enum_ident = spec.ident;
+    let kind_ident = spec.kind_ident();
+    let variants = spec.variants();
+    let cfgs = spec.cfgs();
+    let wrapped = spec.wrapped();
+    let wrapped_variants = wrapped.iter().map(|member| &member.variant);
+    let wrapped_cfgs = wrapped.iter().map(|member| member.cfg_attr());
+    let bind_ref_e = spec.config.member_fields(quote!(ref e));
+    let (severity, _) = features::severity(spec);
+    let member = quote! {
+        match *self {
+            #(
+                #wrapped_cfgs
+                #enum_ident::#wrapped_variants #bind_ref_e => Some(e as &dyn ::core::any::Any),
+            )*
+            _ => None,
+        }
+    };
+    let (access, composed_trait) = match &spec.config.composed_trait {
+        Some(module) => (
+            TokenStream2::new(),
+            quote! {
+                impl #module::ComposedError for #enum_ident {
+                    #[allow(unreachable_patterns)]
+                    fn member(&self) -> Option<&dyn ::core::any::Any> {
+                        #member
+                    }
+                }
+            },
+        ),
+        None => (
+            inherent_access(member, spec.config.boxed),
+            TokenStream2::new(),
+        ),
+    };
+    quote! {
+        impl #enum_ident {
+            /// The kind of error held, as a fieldless enum.
+            pub fn kind(&self) -> #kind_ident {
+                match *self {
+                    #(#cfgs #enum_ident::#variants { .. } => #kind_ident::#variants,)*
+                }
+            }
+
+            /// Whether the operation may succeed if retried, members are annotated `retryable`.
+            pub fn is_retryable(&self) -> bool {
+                self.kind().is_retryable()
+            }
+
+            #severity
+
+            #access
+        }
+
+        #composed_trait
+    }
+}
+
+// `get()`, `chain()`, `as_dyn_error()` and with `boxed` `into_boxed()`, on the enum itself
+fn inherent_access(member: TokenStream2, boxed: bool) -> TokenStream2 {
+    let into_boxed = boxed.then(|| {
+        quote! {
+            /// The error boxed as a trait object, for APIs taking `Box<dyn Error + Send + Sync>`.
+            /// Only callable when every member is `Send` and `Sync`.
+            pub fn into_boxed(self) -> ::std::boxed::Box<dyn ::core::error::Error + Send + Sync>
+            where
+                // Higher ranked, so the bound is checked where called rather than here
+                for<'a> Self: Send + Sync,
+            {
+                ::std::boxed::Box::new(self)
+            }
+        }
+    });
+    quote! {
+        /// Returns a reference to the inner error if it is of type `E`,
+        /// like `downcast_ref` on `dyn Error`.
+        pub fn get<E: 'static>(&self) -> Option<&E> {
+            #[allow(unreachable_patterns)]
+            let member: Option<&dyn ::core::any::Any> = #member;
+            member?.downcast_ref::<E>()
+        }
+
+        /// Iterates over the error and its sources, outermost first, like anyhow's `chain()`.
+        pub fn chain(&self) -> impl Iterator<Item = &(dyn ::core::error::Error + 'static)> {
+            let mut next: Option<&(dyn ::core::error::Error + 'static)> = Some(self);
+            ::core::iter::from_fn(move || {
+                let current = next?;
+                next = current.source();
+                Some(current)
+            })
+        }
+
+        /// The error as a trait object, for APIs taking `&dyn Error`.
+        pub fn as_dyn_error(&self) -> &(dyn ::core::error::Error + 'static) {
+            self
+        }
+
+        #into_boxed
+    }
+}
+
+// `From` the raw type, through the member's own conversion. Calling the member's
+// From impl directly keeps the caller's location with track_caller.
+fn via_impls(spec: &EnumSpec) -> TokenStream2 {
+    let enum_ident = spec.ident;
+    let config = spec.config;
+    let wrapped = spec.wrapped();
+    let via_impls = config.vias.iter().map(|via| {
+        let ViaConversion { from, member, cfg } = via;
+        let cfg = cfg.as_ref().map(|predicate| quote!(#[cfg(#predicate)]));
+        let is_member = wrapped
+            .iter()
+            .any(|wrapped| quote!(#member).to_string() == wrapped.ty.to_token_stream().to_string());
+        if !is_member {
+            return syn::Error::new_spanned(
+                member,
+                "via needs a member of the errorset to convert through",
+            )
+            .to_compile_error();
+        }
+        let track_caller = config.track_caller.then(|| quote!(#[track_caller]));
+        quote! {
+            #cfg
+            impl From<#from> for #enum_ident {
+                #track_caller
+                fn from(source: #from) -> Self {
+                    <#enum_ident as From<#member>>::from(<#member as From<#from>>::from(source))
+                }
+            }
+        }
+    });
+    quote!(#(#via_impls)*)
+}
+
+// An empty set can't be built, so the enum is never-like, for functions that can't
+// fail yet but keep a signature that may
+fn never_impls(spec: &EnumSpec, unreachable: TokenStream2) -> TokenStream2 {
+    if !spec.members.is_empty() {
+        return TokenStream2::new();
+    }
+    let enum_ident = spec.ident;
+    quote! {
+        impl #enum_ident {
+            /// The enum has no variants, so no value of it exists to call this on.
+            pub fn unreachable(self) -> ! {
+                #unreachable
+            }
+        }
+
+        impl From<#enum_ident> for ::core::convert::Infallible {
+            fn from(error: #enum_ident) -> Self {
+                error.unreachable()
+            }
+        }
+    }
+}
+
+// Generic code instantiated with an error that can't happen widens into the enum too,
+// unless that is a member already
+fn infallible_from(spec: &EnumSpec) -> TokenStream2 {
+    let has_infallible = spec.wrapped().iter().any(|member| match &member.ty {
+        Type::Path(tp) => tp.path.segments.last().unwrap().ident == "Infallible",
+        _ => false,
+    });
+    if has_infallible {
+        return TokenStream2::new();
+    }
+    let enum_ident = spec.ident;
+    quote! {
+        impl From<::core::convert::Infallible> for #enum_ident {
+            fn from(never: ::core::convert::Infallible) -> Self {
+                match never {}
+            }
+        }
+    }
+}
+
+// A generic From would overlap the members' impls, so `dyn` members get a constructor
+fn dyn_constructors(spec: &EnumSpec) -> TokenStream2 {
+    let enum_ident = spec.ident;
+    let dyn_constructors = spec.members.iter().filter_map(|member| {
+        let dyn_trait = member.dyn_trait.as_ref()?;
+        let cfg = member.cfg_attr();
+        let ty = &member.ty;
+        let track_caller = spec.config.track_caller.then(|| quote!(#[track_caller]));
+        Some(quote! {
+            #cfg
+            impl #enum_ident {
+                /// Boxes any error implementing
+                #[doc = concat!("`", stringify!(#dyn_trait), "`")]
+                /// into the `Dyn` variant.
+                #track_caller
+                pub fn from_dyn(error: impl #dyn_trait + Send + Sync + 'static) -> Self {
+                    let boxed: #ty = ::std::boxed::Box::new(error);
+                    <Self as From<#ty>>::from(boxed)
+                }
+            }
+        })
+    });
+    quote!(#(#dyn_constructors)*)
+}
+
+// Places the items of a composed enum in a private module, re-exporting them.
+// The enum also implements a marker trait, that can't be implemented elsewhere.
+pub(crate) fn seal_items(
+    enum_ident: &Ident,
+    items: TokenStream2,
+    vis: &syn::Visibility,
+) -> TokenStream2 {
+    let module = name_sealed_module(enum_ident);
+    let marker = name_sealed_marker(enum_ident);
+    quote! {
+        mod #module {
+            use super::*;
+
+            mod sealed {
+                pub trait Sealed {}
+            }
+
+            #items
+
+            /// Marker of
+            #[doc = concat!("[`", stringify!(#enum_ident), "`],")]
+            /// sealed so only the crate defining the enum can implement it.
+            pub trait #marker: sealed::Sealed {}
+
+            impl sealed::Sealed for #enum_ident {}
+            impl #marker for #enum_ident {}
+        }
+        #vis use #module::*;
+    }
+}
+
+// Conversions between the enums composed in the same scope, that share members.
+// A subset widens into its superset with `From`, partial overlaps get a `TryFrom`.
+pub(crate) fn compose_sibling_conversions(
+    functions: &[FuncErrors],
+    name_enum: impl Fn(&FuncErrors) -> Ident,
+    config: &ComposeConfig,
+) -> TokenStream2 {
+    // Any captured location travels along
+    let fields = config
+        .backend
+        .codegen()
+        .variant_fields(quote!(e), config.track_caller.then(|| quote!(location)));
+    let mut conversions = Vec::new();
+    // The opaque structs convert by kind, see `compose_opaque_error`
+    let mut push = |fn_cfgs: &[Attribute], full: TokenStream2, opaque: TokenStream2| {
+        if let Some((full_gate, opaque_gate)) = config.opaque_gates() {
+            conversions.push(quote! {
+                #(#fn_cfgs)*
+                #full_gate
+                #full
+
+                #(#fn_cfgs)*
+                #opaque_gate
+                #opaque
+            });
+        } else {
+            conversions.push(quote!(#(#fn_cfgs)* #full));
+        }
+    };
+    for source_fn in functions {
+        for target_fn in functions {
+            // Newtypes have no variants to convert between
+            if source_fn.name == target_fn.name || source_fn.newtype || target_fn.newtype {
+                continue;
+            }
+            let (source_set, target_set) = (&source_fn.error_set, &target_fn.error_set);
+            let source_ident = name_enum(source_fn);
+            let target_ident = name_enum(target_fn);
+            let source_kind = name_error_kind(&source_ident);
+            let target_kind = name_error_kind(&target_ident);
+            // Both enums need to exist
+            let fn_cfgs: Vec<Attribute> = source_fn
+                .cfgs
+                .iter()
+                .chain(&target_fn.cfgs)
+                .cloned()
+                .collect();
+            // Pairs of (source variant, target variant) holding the same type, and
+            // the source's cfg, under which the target variant exists too.
+            let mut source_variants = Vec::new();
+            let mut target_variants = Vec::new();
+            let mut cfgs = Vec::new();
+            for source in source_set {
+                // A type under several variants goes to the one of the same name, if any
+                let candidates = || {
+                    target_set
+                        .iter()
+                        .filter(|target| target.same_type(source) && target.available_with(source))
+                };
+                let Some(target) = candidates()
+                    .find(|target| target.variant == source.variant)
+                    .or_else(|| candidates().next())
+                else {
+                    continue;
+                };
+                source_variants.push(&source.variant);
+                target_variants.push(&target.variant);
+                cfgs.push(source.cfg_attr());
+            }
+            if source_variants.len() == source_set.len() {
+                let full = quote! {
+                    impl From<#source_ident> for #target_ident {
+                        fn from(value: #source_ident) -> Self {
+                            match value {
+                                #(#cfgs #source_ident::#source_variants #fields => #target_ident::#target_variants #fields,)*
+                            }
+                        }
+                    }
+                };
+                let opaque = quote! {
+                    impl From<#source_ident> for #target_ident {
+                        fn from(value: #source_ident) -> Self {
+                            let kind = match value.kind() {
+                                #(#cfgs #source_kind::#source_variants => #target_kind::#target_variants,)*
+                            };
+                            #target_ident { kind }
+                        }
+                    }
+                };
+                push(&fn_cfgs, full, opaque);
+            } else if !source_variants.is_empty()
+                && !config.no_try_from
+                && !config.minimal
+                && !source_fn.no_try_from
+            {
+                let full = quote! {
+                    impl TryFrom<#source_ident> for #target_ident {
+                        // On a variant missing from the target, the original error is handed back
+                        type Error = #source_ident;
+                        fn try_from(value: #source_ident) -> Result<Self, Self::Error> {
+                            match value {
+                                #(#cfgs #source_ident::#source_variants #fields => Ok(#target_ident::#target_variants #fields),)*
+                                other => Err(other),
+                            }
+                        }
+                    }
+                };
+                let opaque = quote! {
+                    impl TryFrom<#source_ident> for #target_ident {
+                        type Error = #source_ident;
+                        fn try_from(value: #source_ident) -> Result<Self, Self::Error> {
+                            let kind = match value.kind() {
+                                #(#cfgs #source_kind::#source_variants => #target_kind::#target_variants,)*
+                                _ => return Err(value),
+                            };
+                            Ok(#target_ident { kind })
+                        }
+                    }
+                };
+                push(&fn_cfgs, full, opaque);
+            }
+        }
+    }
+    quote!(#(#conversions)*)
+}
+
+// `From` the enum of an inherited supertrait method, variant to variant, for `?` on the
+// supertrait's methods. Both enums are taken to be composed with the same options, and the
+// opaque structs convert by kind.
+pub(crate) fn compose_parent_conversion(
+    enum_ident: &Ident,
+    error_set: &[ErrorMember],
+    parent: &ParentConversion,
+    config: &ComposeConfig,
+) -> TokenStream2 {
+    let ParentConversion {
+        cfgs,
+        enum_path,
+        variants,
+    } = parent;
+    let mut kind_path = enum_path.clone();
+    let kind_segment = kind_path.segments.last_mut().unwrap();
+    kind_segment.ident = name_error_kind(&kind_segment.ident);
+    let kind_ident = name_error_kind(enum_ident);
+    let members: Vec<&ErrorMember> = variants
+        .iter()
+        .filter_map(|variant| error_set.iter().find(|member| &member.variant == variant))
+        .collect();
+    let member_cfgs: Vec<_> = members.iter().map(|member| member.cfg_attr()).collect();
+    let variants: Vec<_> = members.iter().map(|member| &member.variant).collect();
+    let location = config.track_caller.then(|| quote!(location));
+    let fields: Vec<_> = members
+        .iter()
+        .map(|member| match &member.inline {
+            Some(inline) => {
+                let names = inline.field_names();
+                quote!({ #(#names),* })
+            }
+            None => config
+                .backend
+                .codegen()
+                .variant_fields(quote!(error), location.clone()),
+        })
+        .collect();
+    let full = quote! {
+        #(#cfgs)*
+        impl From<#enum_path> for #enum_ident {
+            fn from(value: #enum_path) -> Self {
+                match value {
+                    #(#member_cfgs #enum_path::#variants #fields => #enum_ident::#variants #fields,)*
+                }
+            }
+        }
+    };
+    let Some((full_gate, opaque_gate)) = config.opaque_gates() else {
+        return full;
+    };
+    quote! {
+        #full_gate
+        #full
+
+        #(#cfgs)*
+        #opaque_gate
+        impl From<#enum_path> for #enum_ident {
+            fn from(value: #enum_path) -> Self {
+                let kind = match value.kind() {
+                    #(#member_cfgs #kind_path::#variants => #kind_ident::#variants,)*
+                };
+                #enum_ident { kind }
+            }
+        }
+    }
+}
@@ -0,0 +1,682 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse::Parse, parse_quote, Attribute, Ident, Type};
+
+use crate::{
+    name_composed_error, snake_to_pascal, BackendKind, FuncErrors, ParentConversion, ViaConversion,
+};
+
+// Arguments of the #[compose_errors(...)] attribute, applying to the whole scope
+#[derive(Clone)]
+pub(crate) struct ComposeConfig {
+    // Visibility of the generated types
+    pub(crate) vis: syn::Visibility,
+    // Derives added to the composed enums, besides Error and Debug
+    pub(crate) derives: Vec<syn::Path>,
+    // `#[serde(..)]` container attributes for those derives, as in `serde(tag = "type")`
+    pub(crate) serde_attrs: Vec<TokenStream2>,
+    // Derives added to the kind enums only, fieldless so any derive applies, as schema ones
+    pub(crate) kind_derives: Vec<syn::Path>,
+    // Conversions through a member, `via(RawDbError => DbError)`, from the errorset
+    pub(crate) vias: Vec<ViaConversion>,
+    // Enums of the supertrait methods an errorset inherits from, converting into this one
+    pub(crate) parents: Vec<ParentConversion>,
+    // The `{Enum}Context` extension trait, for the errorset's `Context` variant
+    pub(crate) context: bool,
+    // The function the enum is composed for, linked from its docs
+    pub(crate) origin: Option<syn::Path>,
+    // Template of the enum names, with `{scope}` and `{fn}` placeholders
+    pub(crate) naming: Option<String>,
+    // Module to place the generated items in
+    pub(crate) module: Option<Ident>,
+    // A scope registered with `register_error_scope!`, naming the enums in place of the item
+    pub(crate) scope: Option<syn::Path>,
+    // Tells apart impl blocks of the same type, `FooReadImpl` with `block = "Read"`
+    pub(crate) block: Option<Ident>,
+    // Integer repr of the enums and their kinds, allowing explicit discriminants
+    pub(crate) repr: Option<Ident>,
+    // A C ABI error code enum for each enum, with `to_ffi()`
+    pub(crate) ffi: bool,
+    // Path of the thiserror crate, for when it is re-exported or renamed
+    pub(crate) crate_path: syn::Path,
+    // Variants carry the location where the error entered the enum
+    pub(crate) track_caller: bool,
+    // `fn(&dyn Error)` the From impls of the members call with the new enum, to capture
+    // errors to an error tracker as they enter the set
+    pub(crate) report_hook: Option<syn::Path>,
+    // Debug prints the variant name and the Display of the error, `IoError: not found`
+    pub(crate) compact_debug: bool,
+    // Derive `arbitrary::Arbitrary`, needs the `arbitrary` feature
+    pub(crate) arbitrary: bool,
+    // Order variants alphabetically, rather than as written
+    pub(crate) sorted: bool,
+    // Exit codes, hints and user messages for command line apps
+    pub(crate) cli: bool,
+    // `async_graphql::ErrorExtensions`, needs the `async-graphql` feature
+    pub(crate) graphql: bool,
+    // A `MockFoo` test double of the trait, see `compose_trait_mock`
+    pub(crate) mock: bool,
+    // An `ERROR_CATALOG` const describing the scope's enums, see `compose_error_catalog`
+    pub(crate) catalog: bool,
+    // Module of the catalog types declared with `error_catalog_types!`, shared by scopes
+    pub(crate) catalog_types: Option<syn::Path>,
+    // Keep the enums in private modules, with non_exhaustive variants and a sealed marker
+    pub(crate) sealed: bool,
+    // Derive `Copy` and `Clone`, asserting each member is `Copy`
+    pub(crate) copy: bool,
+    // Derive `defmt::Format`, needs the `defmt` feature
+    pub(crate) defmt: bool,
+    // Derive `uniffi::Error` as a flat error, needs the `uniffi` feature
+    pub(crate) uniffi: bool,
+    // The crate deriving the error impls
+    pub(crate) backend: BackendKind,
+    // Under this cfg predicate, each enum is replaced by an opaque struct holding only the
+    // kind, see `compose_opaque_error`. The predicate is the user's, `opaque(feature = "..")`.
+    pub(crate) opaque: Option<TokenStream2>,
+    // Variant names as strings, and iteration over kinds
+    pub(crate) variant_names: bool,
+    // Conversions to and from `terrors::OneOf`, needs the `terrors` feature
+    pub(crate) terrors: bool,
+    // A `FooResult<T>` alias for each `FooError`
+    pub(crate) result_alias: bool,
+    // A `FooErrorWiden` trait, with `.widen::<Wider>()` on `Result<T, FooError>`
+    pub(crate) widen: bool,
+    // Skip the reverse `TryFrom` impls, from the enum back to its members
+    pub(crate) no_try_from: bool,
+    // Only the enums and their `From` impls, for crates where expansion size matters
+    pub(crate) minimal: bool,
+    // A `FooErrorVisitor` trait with a method per variant, and `FooError::visit`
+    pub(crate) visitor: bool,
+    // A `match_foo_error!` macro matching on variants by their snake case names
+    pub(crate) match_macro: bool,
+    // A `FooError::io_error(e)` constructor per variant
+    pub(crate) constructors: bool,
+    // `foo_error_bail!` and `foo_error_ensure!` macros returning early with the enum
+    pub(crate) bail_macros: bool,
+    // `report()` rendering the error and its sources over several lines
+    pub(crate) report: bool,
+    // `to_json()` rendering the error and its sources as JSON, needs the `serde-json` feature
+    pub(crate) json: bool,
+    // `to_problem()` as RFC 7807 problem details, with the module declaring the type with
+    // `problem_details_type!`, `crate` unless given. Needs the `problem-details` feature.
+    pub(crate) problem: Option<syn::Path>,
+    // `otel_attributes()` as OpenTelemetry key-values, needs the `otel` feature
+    pub(crate) otel: bool,
+    // `severity()` on every enum, with the module declaring the type with `severity_type!`,
+    // `crate` unless given. Members annotated `warning` or `fatal` need it.
+    pub(crate) severity: Option<syn::Path>,
+    // Equality, hashing and ordering comparing only the kind, for members without them
+    pub(crate) kind_eq: bool,
+    // `into_boxed()`, boxing the enum as `Box<dyn Error + Send + Sync>`, which needs std
+    pub(crate) boxed: bool,
+    // `get()`, `chain()` and `as_dyn_error()` from the `ComposedError` trait declared with
+    // `composed_error_trait!`, in the module given, `crate` unless given
+    pub(crate) composed_trait: Option<syn::Path>,
+    // Warn about members the function body never produces
+    pub(crate) check_usage: bool,
+    // A module per scope re-exporting its composed types, for one glob import
+    pub(crate) index: bool,
+    // On `impl Trait for Type`, fill in the enums composed for the trait's methods
+    pub(crate) use_trait: bool,
+    // The errorset of the methods returning `Result<T, _>` without one of their own
+    pub(crate) default_errorset: Option<TokenStream2>,
+    // Aliases of `Result<T, E>` taking the composed error too, like `anyhow::Result`
+    pub(crate) result_types: Vec<syn::Path>,
+}
+
+impl Default for ComposeConfig {
+    fn default() -> Self {
+        ComposeConfig {
+            vis: parse_quote!(pub),
+            derives: Vec::new(),
+            serde_attrs: Vec::new(),
+            kind_derives: Vec::new(),
+            vias: Vec::new(),
+            parents: Vec::new(),
+            context: false,
+            origin: None,
+            naming: None,
+            module: None,
+            scope: None,
+            block: None,
+            repr: None,
+            ffi: false,
+            crate_path: parse_quote!(::thiserror),
+            track_caller: false,
+            report_hook: None,
+            compact_debug: false,
+            arbitrary: false,
+            sorted: false,
+            cli: false,
+            graphql: false,
+            mock: false,
+            catalog: false,
+            catalog_types: None,
+            sealed: false,
+            copy: false,
+            defmt: false,
+            uniffi: false,
+            backend: BackendKind::default(),
+            opaque: None,
+            variant_names: false,
+            terrors: false,
+            result_alias: false,
+            widen: false,
+            no_try_from: false,
+            minimal: false,
+            visitor: false,
+            match_macro: false,
+            constructors: false,
+            bail_macros: false,
+            report: false,
+            json: false,
+            problem: None,
+            otel: false,
+            severity: None,
+            kind_eq: false,
+            boxed: false,
+            composed_trait: None,
+            check_usage: false,
+            index: false,
+            use_trait: false,
+            default_errorset: None,
+            result_types: Vec::new(),
+        }
+    }
+}
+
+impl ComposeConfig {
+    // The config for one function, with the overrides of its errorset applied
+    pub(crate) fn for_function(&self, func: &FuncErrors) -> ComposeConfig {
+        let mut config = self.clone();
+        config.no_try_from |= func.no_try_from;
+        config.copy |= func.copy;
+        config.sorted |= func.sorted;
+        if let Some(vis) = &func.vis {
+            config.vis = vis.clone();
+        }
+        config.derives.extend(func.derives.iter().cloned());
+        config.vias.extend(func.vias.iter().cloned());
+        config.parents.extend(func.parents.iter().cloned());
+        config.context |= func.context;
+        config.origin.clone_from(&func.origin);
+        config
+    }
+
+    // Name of the enum composed for a function. Unless the errorset names it,
+    // follows the `naming` template, by default `{scope}{fn}Error`.
+    pub(crate) fn name_enum(&self, func: &FuncErrors, scope_name: &str) -> Ident {
+        self.name_enum_for(&func.name, scope_name, func.enum_name.as_ref())
+    }
+
+    pub(crate) fn name_enum_for(
+        &self,
+        function_ident: &Ident,
+        scope_name: &str,
+        enum_name: Option<&Ident>,
+    ) -> Ident {
+        if let Some(enum_name) = enum_name {
+            return enum_name.clone();
+        }
+        match &self.naming {
+            Some(naming) => {
+                let name = naming
+                    .replace("{scope}", &snake_to_pascal(scope_name))
+                    .replace("{fn}", &snake_to_pascal(&function_ident.to_string()));
+                Ident::new(&name, function_ident.span())
+            }
+            None => name_composed_error(function_ident, scope_name),
+        }
+    }
+
+    // `#[repr(u8)]`, with the repr option
+    pub(crate) fn repr_attr(&self) -> TokenStream2 {
+        match &self.repr {
+            Some(repr) => quote!(#[repr(#repr)]),
+            None => TokenStream2::new(),
+        }
+    }
+
+    // The value a member's From impl returns, handed to the report_hook first
+    pub(crate) fn reported(&self, value: TokenStream2) -> TokenStream2 {
+        match &self.report_hook {
+            Some(hook) => quote!({
+                let error = #value;
+                #hook(&error);
+                error
+            }),
+            None => value,
+        }
+    }
+
+    // The registered scope's name, when set, in place of the one of the item
+    pub(crate) fn scope_name_or(&self, item_scope: String) -> String {
+        match &self.scope {
+            Some(scope) => scope.segments.last().unwrap().ident.to_string(),
+            None => item_scope,
+        }
+    }
+
+    // The composed enum as a type, from where the annotated functions are.
+    // Built directly, as this runs for every annotated function.
+    pub(crate) fn enum_type(&self, enum_ident: &Ident) -> Type {
+        let mut path = syn::Path::from(enum_ident.clone());
+        if let Some(module) = &self.module {
+            path.segments.insert(0, module.clone().into());
+        }
+        Type::Path(syn::TypePath { qself: None, path })
+    }
+
+    // The `Severity` declared with `severity_type!` that `severity()` returns, with the
+    // `severity` option
+    pub(crate) fn severity_type(&self) -> Option<syn::Path> {
+        let module = self.severity.as_ref()?;
+        Some(parse_quote!(#module::Severity))
+    }
+
+    // The cfgs of the enum and of the opaque struct standing in for it, with `opaque(..)`
+    pub(crate) fn opaque_gates(&self) -> Option<(Attribute, Attribute)> {
+        let predicate = self.opaque.as_ref()?;
+        Some((
+            parse_quote!(#[cfg(not(#predicate))]),
+            parse_quote!(#[cfg(#predicate)]),
+        ))
+    }
+
+    // Type of the enum in signatures, through the module re-exporting it with `in path`
+    pub(crate) fn signature_type(&self, enum_ident: &Ident, within: Option<&syn::Path>) -> Type {
+        match within {
+            Some(within) => parse_quote!(#within::#enum_ident),
+            None => self.enum_type(enum_ident),
+        }
+    }
+
+    // Pattern for the fields of a wrapping variant, binding the member error
+    // and ignoring the rest
+    pub(crate) fn member_fields(&self, binding: TokenStream2) -> TokenStream2 {
+        let location = self.track_caller.then(|| quote!(_));
+        self.backend.codegen().variant_fields(binding, location)
+    }
+
+    pub(crate) fn parse_arg(&mut self, meta: syn::meta::ParseNestedMeta) -> syn::Result<()> {
+        let Some(option) = meta.path.get_ident().map(Ident::to_string) else {
+            return Err(meta.error("unsupported compose_errors argument"));
+        };
+        // Integrations need the feature of composerr generating them
+        if let Some((feature, false)) = required_feature(&option) {
+            return Err(meta.error(format!(
+                "enable the `{feature}` feature of composerr to use this"
+            )));
+        }
+        if let Some(flag) = self.flag(&option) {
+            *flag = true;
+            return Ok(());
+        }
+        match option.as_str() {
+            "vis" => self.vis = parse_meta_value(&meta)?,
+            "derive" => meta.parse_nested_meta(|derive| {
+                self.derives.push(derive.path);
+                Ok(())
+            })?,
+            "kind_derive" => meta.parse_nested_meta(|derive| {
+                self.kind_derives.push(derive.path);
+                Ok(())
+            })?,
+            "serde" => {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                self.serde_attrs.push(content.parse()?);
+            }
+            "skip" => {
+                return Err(meta.error("skip opts an item out of its module's `#[compose_errors]`"))
+            }
+            "result_types" => meta.parse_nested_meta(|alias| {
+                self.result_types.push(alias.path);
+                Ok(())
+            })?,
+            "default_errorset" => {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                self.default_errorset = Some(content.parse()?);
+            }
+            "naming" => self.naming = Some(parse_naming(&meta)?),
+            "crate_path" => self.crate_path = parse_meta_value(&meta)?,
+            "module" => self.module = Some(parse_meta_value(&meta)?),
+            "repr" => self.repr = Some(parse_repr(&meta)?),
+            "block" => self.block = Some(parse_meta_value(&meta)?),
+            "scope" => self.scope = Some(parse_meta_value(&meta)?),
+            "report_hook" => self.report_hook = Some(parse_meta_value(&meta)?),
+            "opaque" => {
+                let predicate;
+                syn::parenthesized!(predicate in meta.input);
+                let predicate: TokenStream2 = predicate.parse()?;
+                if predicate.is_empty() {
+                    return Err(meta.error(
+                        "opaque takes the cfg predicate to swap the enums under, `opaque(feature = \"small-errors\")`",
+                    ));
+                }
+                self.opaque = Some(predicate);
+            }
+            "catalog" => {
+                self.catalog = true;
+                if meta.input.peek(syn::Token![=]) {
+                    self.catalog_types = Some(parse_meta_value(&meta)?);
+                }
+            }
+            // Shared types declared by the user, in the crate root unless given a path
+            "severity" => self.severity = Some(parse_types_path(&meta)?),
+            "problem" => self.problem = Some(parse_types_path(&meta)?),
+            "composed_trait" => self.composed_trait = Some(parse_types_path(&meta)?),
+            "snafu" => self.backend = BackendKind::Snafu,
+            "backend" => self.backend = parse_backend(&meta)?,
+            _ => return Err(meta.error("unsupported compose_errors argument")),
+        }
+        Ok(())
+    }
+
+    // The field of an option set by naming it alone
+    fn flag(&mut self, option: &str) -> Option<&mut bool> {
+        Some(match option {
+            "track_caller" => &mut self.track_caller,
+            "compact_debug" => &mut self.compact_debug,
+            "variant_names" => &mut self.variant_names,
+            "no_try_from" => &mut self.no_try_from,
+            "minimal" => &mut self.minimal,
+            "visitor" => &mut self.visitor,
+            "match_macro" => &mut self.match_macro,
+            "constructors" => &mut self.constructors,
+            "bail_macros" => &mut self.bail_macros,
+            "report" => &mut self.report,
+            "kind_eq" => &mut self.kind_eq,
+            "boxed" => &mut self.boxed,
+            "check_usage" => &mut self.check_usage,
+            "index" => &mut self.index,
+            "use_trait" => &mut self.use_trait,
+            "result_alias" => &mut self.result_alias,
+            "widen" => &mut self.widen,
+            "cli" => &mut self.cli,
+            "ffi" => &mut self.ffi,
+            "mock" => &mut self.mock,
+            "sorted" => &mut self.sorted,
+            "sealed" => &mut self.sealed,
+            "copy" => &mut self.copy,
+            "terrors" => &mut self.terrors,
+            "json" => &mut self.json,
+            "otel" => &mut self.otel,
+            "graphql" => &mut self.graphql,
+            "defmt" => &mut self.defmt,
+            "uniffi" => &mut self.uniffi,
+            "arbitrary" => &mut self.arbitrary,
+            _ => return None,
+        })
+    }
+
+    // Checks the arguments make sense together
+    pub(crate) fn validate(&self) -> syn::Result<()> {
+        if self.arbitrary && self.track_caller {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "arbitrary can't be combined with track_caller, locations can't be generated",
+            ));
+        }
+        if self.backend == BackendKind::Snafu && self.track_caller {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "snafu can't be combined with track_caller, use snafu's own implicit locations",
+            ));
+        }
+        if self.defmt && self.track_caller {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "defmt can't be combined with track_caller, locations don't implement defmt::Format",
+            ));
+        }
+        if self.opaque.is_some()
+            && (self.track_caller
+                || self.backend == BackendKind::Snafu
+                || self.terrors
+                || self.arbitrary
+                || self.uniffi)
+        {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "opaque can't be combined with track_caller, snafu, terrors, arbitrary or uniffi",
+            ));
+        }
+        if self.opaque.is_some() && (self.visitor || self.match_macro) {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "visitor and match_macro can't be combined with opaque, the opaque struct has no errors to visit",
+            ));
+        }
+        if self.boxed && self.composed_trait.is_some() {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "boxed can't be combined with composed_trait, which has `into_boxed()` when declared with `composed_error_trait!(boxed)`",
+            ));
+        }
+        if self.minimal
+            && (self.opaque.is_some()
+                || self.cli
+                || self.graphql
+                || self.ffi
+                || self.catalog
+                || self.variant_names
+                || self.result_alias
+                || self.widen
+                || self.visitor
+                || self.match_macro
+                || self.constructors
+                || self.bail_macros
+                || self.report
+                || self.json
+                || self.problem.is_some()
+                || self.otel
+                || self.severity.is_some()
+                || self.kind_eq
+                || self.boxed
+                || self.composed_trait.is_some()
+                || !self.kind_derives.is_empty()
+                || !self.kind_derives.is_empty()
+                || self.terrors)
+        {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "minimal only generates the enums and their From impls, it can't be combined with options adding helpers",
+            ));
+        }
+        Ok(())
+    }
+
+    // `errorset(newtype)` composes a plain struct around the member, with none of the
+    // enum's machinery to hang these options on
+    pub(crate) fn validate_newtype(&self, span: proc_macro2::Span) -> syn::Result<()> {
+        if self.track_caller || self.sealed || self.minimal {
+            return Err(syn::Error::new(
+                span,
+                "errorset(newtype) can't be combined with track_caller, sealed or minimal",
+            ));
+        }
+        if self.opaque.is_some()
+            || self.cli
+            || self.graphql
+            || self.ffi
+            || self.variant_names
+            || self.widen
+            || self.visitor
+            || self.match_macro
+            || self.constructors
+            || self.bail_macros
+            || self.report
+            || self.json
+            || self.problem.is_some()
+            || self.otel
+            || self.severity.is_some()
+            || self.kind_eq
+            || self.boxed
+            || self.composed_trait.is_some()
+            || self.terrors
+            || self.arbitrary
+            || self.defmt
+            || self.uniffi
+        {
+            return Err(syn::Error::new(
+                span,
+                "errorset(newtype) only wraps its member, it can't be combined with options adding helpers to the enum",
+            ));
+        }
+        Ok(())
+    }
+
+    // Extra derives for the composed enum and its kind enum
+    pub(crate) fn extra_derives(&self) -> TokenStream2 {
+        let mut derives = Vec::new();
+        if self.arbitrary {
+            derives.push(quote!(::arbitrary::Arbitrary));
+        }
+        if self.defmt {
+            derives.push(quote!(::defmt::Format));
+        }
+        if derives.is_empty() {
+            TokenStream2::new()
+        } else {
+            quote!(#[derive(#(#derives),*)])
+        }
+    }
+
+    // Derive for the generated types that only ever hold plain data
+    pub(crate) fn defmt_derive(&self) -> TokenStream2 {
+        if self.defmt {
+            quote!(#[derive(::defmt::Format)])
+        } else {
+            TokenStream2::new()
+        }
+    }
+
+    // uniffi sees the composed enum as a flat error, its variants carrying the Display message
+    pub(crate) fn uniffi_attrs(&self) -> TokenStream2 {
+        if self.uniffi {
+            quote!(#[derive(::uniffi::Error)] #[uniffi(flat_error)])
+        } else {
+            TokenStream2::new()
+        }
+    }
+
+    // The serde container attributes, for the composed enum only
+    pub(crate) fn serde_attrs(&self) -> TokenStream2 {
+        let serde_attrs = &self.serde_attrs;
+        quote!(#(#[serde(#serde_attrs)])*)
+    }
+
+    // The user requested derives, for the composed enum only
+    pub(crate) fn user_derives(&self) -> TokenStream2 {
+        let mut derives: Vec<TokenStream2> =
+            self.derives.iter().map(|path| quote!(#path)).collect();
+        if self.copy {
+            if !self.derives.iter().any(|path| path.is_ident("Clone")) {
+                derives.push(quote!(Clone));
+            }
+            if !self.derives.iter().any(|path| path.is_ident("Copy")) {
+                derives.push(quote!(Copy));
+            }
+        }
+        if derives.is_empty() {
+            TokenStream2::new()
+        } else {
+            quote!(#[derive(#(#derives),*)])
+        }
+    }
+}
+
+// The value of a `key = value` argument naming a Rust item, path or visibility.
+// It may be written bare, `module = errors`, or quoted, `module = "errors"`.
+pub(crate) fn parse_meta_value<T: Parse>(meta: &syn::meta::ParseNestedMeta) -> syn::Result<T> {
+    let value = meta.value()?;
+    if value.peek(syn::LitStr) {
+        value.parse::<syn::LitStr>()?.parse()
+    } else {
+        value.parse()
+    }
+}
+
+// The cargo feature of composerr an option needs, and whether it is enabled
+fn required_feature(option: &str) -> Option<(&'static str, bool)> {
+    Some(match option {
+        "terrors" => ("terrors", cfg!(feature = "terrors")),
+        "json" => ("serde-json", cfg!(feature = "serde-json")),
+        "otel" => ("otel", cfg!(feature = "otel")),
+        "problem" => ("problem-details", cfg!(feature = "problem-details")),
+        "graphql" => ("async-graphql", cfg!(feature = "async-graphql")),
+        "defmt" => ("defmt", cfg!(feature = "defmt")),
+        "uniffi" => ("uniffi", cfg!(feature = "uniffi")),
+        "snafu" => ("snafu", cfg!(feature = "snafu")),
+        "arbitrary" => ("arbitrary", cfg!(feature = "arbitrary")),
+        _ => return None,
+    })
+}
+
+// `naming = "{scope}{fn}Error"`, which must give an identifier telling the enums apart
+fn parse_naming(meta: &syn::meta::ParseNestedMeta) -> syn::Result<String> {
+    let naming: syn::LitStr = meta.value()?.parse()?;
+    let sample = naming
+        .value()
+        .replace("{scope}", "Scope")
+        .replace("{fn}", "Fn");
+    if !naming.value().contains("{fn}") || syn::parse_str::<Ident>(&sample).is_err() {
+        return Err(syn::Error::new(
+            naming.span(),
+            "naming must give an identifier, and include `{fn}` to tell enums apart",
+        ));
+    }
+    Ok(naming.value())
+}
+
+// `repr = "u8"`, an integer type
+fn parse_repr(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Ident> {
+    let repr: Ident = parse_meta_value(meta)?;
+    let integers = [
+        "u8", "u16", "u32", "u64", "usize", "i8", "i16", "i32", "i64", "isize",
+    ];
+    if !integers.contains(&repr.to_string().as_str()) {
+        return Err(syn::Error::new(
+            repr.span(),
+            "repr must be an integer type, like \"u8\"",
+        ));
+    }
+    Ok(repr)
+}
+
+// `backend = "..."`, of those whose feature is enabled
+fn parse_backend(meta: &syn::meta::ParseNestedMeta) -> syn::Result<BackendKind> {
+    let backend: syn::LitStr = meta.value()?.parse()?;
+    let (parsed, enabled) =
+        match backend.value().as_str() {
+            "thiserror" => (BackendKind::Thiserror, true),
+            "snafu" => (BackendKind::Snafu, cfg!(feature = "snafu")),
+            "displaydoc" => (BackendKind::Displaydoc, cfg!(feature = "displaydoc")),
+            "plain" => (BackendKind::Plain, true),
+            _ => return Err(syn::Error::new(
+                backend.span(),
+                "unknown backend, expected \"thiserror\", \"snafu\", \"displaydoc\" or \"plain\"",
+            )),
+        };
+    if !enabled {
+        return Err(syn::Error::new(
+            backend.span(),
+            format!(
+                "enable the `{}` feature of composerr to use this",
+                backend.value()
+            ),
+        ));
+    }
+    Ok(parsed)
+}
+
+// The module of types declared with one of composerr's macros, `crate` unless given
+fn parse_types_path(meta: &syn::meta::ParseNestedMeta) -> syn::Result<syn::Path> {
+    if meta.input.peek(syn::Token![=]) {
+        parse_meta_value(meta)
+    } else {
+        Ok(parse_quote!(crate))
+    }
+}
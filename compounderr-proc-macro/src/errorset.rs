@@ -0,0 +1,685 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote, Attribute, Ident, Pat, ReturnType, Type,
+};
+
+use crate::{
+    closest_name, existing_result_error, is_boxed_trait_object, is_relative_errorset,
+    parse_meta_value, ErrorMember, InlineVariant, ParentConversion, Route, ViaConversion,
+};
+
+// The lowercase keywords of #[errorset], suggested for misspelled ones
+pub(crate) const ERRORSET_FLAGS: &[&str] = &[
+    "extend",
+    "throws",
+    "map_err",
+    "typed",
+    "newtype",
+    "no_try_from",
+    "copy",
+    "sorted",
+    "io_with_context",
+    "context",
+    "derive",
+    "default",
+    "cfg",
+    "via",
+    "map",
+    "dyn",
+];
+
+// The stable `std::io::ErrorKind` variants, which `io::Kind` members route on
+const IO_ERROR_KINDS: &[&str] = &[
+    "NotFound",
+    "PermissionDenied",
+    "ConnectionRefused",
+    "ConnectionReset",
+    "HostUnreachable",
+    "NetworkUnreachable",
+    "ConnectionAborted",
+    "NotConnected",
+    "AddrInUse",
+    "AddrNotAvailable",
+    "NetworkDown",
+    "BrokenPipe",
+    "AlreadyExists",
+    "WouldBlock",
+    "NotADirectory",
+    "IsADirectory",
+    "DirectoryNotEmpty",
+    "ReadOnlyFilesystem",
+    "StaleNetworkFileHandle",
+    "InvalidInput",
+    "InvalidData",
+    "TimedOut",
+    "WriteZero",
+    "StorageFull",
+    "NotSeekable",
+    "QuotaExceeded",
+    "FileTooLarge",
+    "ResourceBusy",
+    "ExecutableFileBusy",
+    "Deadlock",
+    "CrossesDevices",
+    "TooManyLinks",
+    "InvalidFilename",
+    "ArgumentListTooLong",
+    "Interrupted",
+    "Unsupported",
+    "UnexpectedEof",
+    "OutOfMemory",
+    "InProgress",
+    "Other",
+];
+
+// Arguments of an #[errorset] helper attribute
+#[derive(Default)]
+pub(crate) struct ErrorsetArgs {
+    // Keep the error type already in the signature as a member, instead of an inferred `_`.
+    // Where it was given, as is `typed`.
+    pub(crate) extend: Option<proc_macro2::Span>,
+    // The function is written returning `T`, and gets `Result<T, _>` with `Ok` wrapping
+    pub(crate) throws: bool,
+    // Every `expr?` in the body becomes `expr.map_err(Composed::from)?`, opted in to
+    pub(crate) map_err: bool,
+    // The signature and its boxed error are kept, a `foo_typed` sibling returns the enum
+    pub(crate) typed: Option<proc_macro2::Span>,
+    // A single member is wrapped in a transparent struct, rather than a one-variant enum
+    pub(crate) newtype: bool,
+    // Overrides of the scope config, for this function only
+    pub(crate) no_try_from: bool,
+    pub(crate) copy: bool,
+    pub(crate) sorted: bool,
+    pub(crate) name: Option<Ident>,
+    pub(crate) vis: Option<syn::Visibility>,
+    pub(crate) derives: Vec<syn::Path>,
+    // `in crate::errors`, the module re-exporting the enum, naming it in the signature
+    pub(crate) within: Option<syn::Path>,
+    // `via(RawDbError => DbError)`, types converting into a member first
+    pub(crate) vias: Vec<ViaConversion>,
+    // The enum of a `super::method` reference, spliced in by `__inherit_errorset` along with
+    // its members
+    pub(crate) parents: Vec<ParentConversion>,
+    // The `Io { source, path, op }` preset member, with its `ResultExt` helper
+    pub(crate) io_with_context: bool,
+    // The `Context { msg, source }` member, with its extension trait
+    pub(crate) context: bool,
+    pub(crate) members: Vec<ErrorMember>,
+    // References to supertrait method errorsets, like `super::method`
+    pub(crate) inherits: Vec<syn::Path>,
+    // Where the arguments failed to parse, the members before it are kept
+    pub(crate) error: Option<syn::Error>,
+}
+
+impl ErrorsetArgs {
+    // The complete error set, given the signature output the args were attached to, and
+    // what makes no sense of it. A typed signature without a Result is left to its sibling.
+    pub(crate) fn error_set(&self, output: &ReturnType) -> (Vec<ErrorMember>, Vec<syn::Error>) {
+        let mut error_set = Vec::new();
+        let mut errors = Vec::new();
+        if let (Some(_), Some(extend)) = (self.typed, self.extend) {
+            errors.push(syn::Error::new(
+                extend,
+                "errorset(typed) keeps the existing error already, it can't be combined with extend",
+            ));
+        }
+        // The boxed error of a typed function holds what no member is downcast to
+        if let (Some(_), Some(existing)) = (self.typed, existing_result_error(output)) {
+            let mut member = ErrorMember::new(
+                Ident::new("Other", proc_macro2::Span::call_site()),
+                existing,
+            );
+            member.dyn_trait = Some(parse_quote!(::std::error::Error));
+            error_set.push(member);
+        }
+        if let (Some(extend), None) = (self.extend, self.typed) {
+            match existing_result_error(output) {
+                // The enum converts into the box already, a member of it can't convert back
+                Some(existing) if is_boxed_trait_object(&existing) => {
+                    errors.push(syn::Error::new_spanned(
+                        existing,
+                        "errorset(extend) can't keep a boxed trait object, use `typed` to downcast it to the members",
+                    ))
+                }
+                Some(Type::Path(tp)) => {
+                    let variant = tp.path.segments.last().unwrap().ident.clone();
+                    error_set.push(ErrorMember::new(variant, Type::Path(tp)));
+                }
+                Some(existing) => errors.push(syn::Error::new_spanned(
+                    existing,
+                    "errorset(extend) needs a named error type to extend",
+                )),
+                None => errors.push(syn::Error::new(
+                    extend,
+                    "errorset(extend) needs a `Result<T, E>` return type to extend",
+                )),
+            }
+        }
+        if self.io_with_context {
+            error_set.push(io_with_context_member());
+        }
+        if self.context {
+            error_set.push(context_member());
+        }
+        error_set.extend(self.members.iter().cloned());
+        for member in &error_set {
+            if member.mapper.is_some() && (member.inline.is_some() || member.route.is_some()) {
+                errors.push(syn::Error::new_spanned(
+                    member.mapper.as_ref(),
+                    "errorset `map = fn` only applies to members wrapping a type, not inline or routed ones",
+                ));
+            }
+        }
+        // Every error of a routed type converts, what no pattern matches needs somewhere to go
+        for routed in error_set.iter().filter(|member| member.is_routed()) {
+            let has_fallback = error_set
+                .iter()
+                .any(|member| member.is_fallback() && member.same_ty(routed));
+            if has_fallback {
+                continue;
+            }
+            let message = match routed.route {
+                Some(Route::IoKind(_)) => {
+                    "errorset with `io::Kind` members also needs `IoOther`, for the remaining kinds"
+                }
+                _ => "errorset `map` needs a `_ => Variant` arm, for the errors no pattern matches",
+            };
+            errors.push(syn::Error::new(routed.variant.span(), message));
+        }
+        (error_set, errors)
+    }
+
+    // Union with the args of another #[errorset] on the same function
+    pub(crate) fn merge(&mut self, other: ErrorsetArgs) {
+        self.extend = self.extend.or(other.extend);
+        self.throws |= other.throws;
+        self.map_err |= other.map_err;
+        self.typed = self.typed.or(other.typed);
+        self.newtype |= other.newtype;
+        self.no_try_from |= other.no_try_from;
+        self.copy |= other.copy;
+        self.sorted |= other.sorted;
+        self.io_with_context |= other.io_with_context;
+        self.context |= other.context;
+        let conflict = match (&self.name, &other.name) {
+            (Some(name), Some(other_name)) if name != other_name => Some(syn::Error::new(
+                other_name.span(),
+                format!("conflicting errorset names `{name}` and `{other_name}`"),
+            )),
+            _ => None,
+        };
+        self.name = self.name.take().or(other.name);
+        self.vis = self.vis.take().or(other.vis);
+        self.within = self.within.take().or(other.within);
+        self.derives.extend(other.derives);
+        self.vias.extend(other.vias);
+        self.parents.extend(other.parents);
+        self.members.extend(other.members);
+        self.inherits.extend(other.inherits);
+        for other_error in other.error.into_iter().chain(conflict) {
+            match &mut self.error {
+                Some(error) => error.combine(other_error),
+                None => self.error = Some(other_error),
+            }
+        }
+    }
+}
+
+// Whether the attribute is an #[errorset], however the path is spelled
+pub(crate) fn is_errorset_attr(attr: &Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "errorset")
+}
+
+// The args of all the #[errorset] attributes of a function, merged together.
+// None when the function has no #[errorset].
+pub(crate) fn errorset_args(attrs: &[Attribute]) -> Option<ErrorsetArgs> {
+    attrs
+        .iter()
+        .filter(|attr| is_errorset_attr(attr))
+        .map(parse_errorset_args)
+        .reduce(|mut args, other| {
+            args.merge(other);
+            args
+        })
+}
+
+// `#[errorset(..)]`, `#[errorset{..}]` and `#[errorset[..]]` are all the same, arguments being
+// members, `key = value` options and flags. Other shapes are reported.
+pub(crate) fn parse_errorset_args(attr: &Attribute) -> ErrorsetArgs {
+    let mut args = ErrorsetArgs::default();
+    match &attr.meta {
+        syn::Meta::List(_) if is_relative_errorset(attr) => {
+            args.error = Some(syn::Error::new_spanned(
+                attr,
+                "`+Member` and `-Member` are relative to the scope's `default_errorset`",
+            ));
+            return args;
+        }
+        syn::Meta::List(_) => {}
+        syn::Meta::Path(_) => {
+            args.error = Some(syn::Error::new_spanned(
+                attr,
+                "list the members of the set, `#[errorset(..)]`, `#[errorset{..}]` or `#[errorset[..]]`",
+            ));
+            return args;
+        }
+        syn::Meta::NameValue(_) => {
+            args.error = Some(syn::Error::new_spanned(
+                attr,
+                "errorset members go in a list, `#[errorset(..)]`, `#[errorset{..}]` or `#[errorset[..]]`",
+            ));
+            return args;
+        }
+    }
+    let parsed = attr.parse_nested_meta(|meta| {
+        if args.parse_option(&meta)? {
+            return Ok(());
+        }
+        args.parse_members(meta)
+    });
+    args.error = parsed.err();
+    args
+}
+
+impl ErrorsetArgs {
+    // A lowercase option of the errorset, if that is what the entry is
+    fn parse_option(&mut self, meta: &syn::meta::ParseNestedMeta) -> syn::Result<bool> {
+        if meta.path.segments.first().unwrap().ident == "super" {
+            self.inherits.push(meta.path.clone());
+            return Ok(true);
+        }
+        let Some(option) = meta.path.get_ident().map(Ident::to_string) else {
+            return Ok(false);
+        };
+        let needs_body = ["throws", "map_err"].contains(&option.as_str());
+        if needs_body && !cfg!(feature = "body-analysis") {
+            return Err(meta.error("enable the `body-analysis` feature of composerr to use this"));
+        }
+        match option.as_str() {
+            "extend" => self.extend = Some(meta.path.segments[0].ident.span()),
+            "typed" => self.typed = Some(meta.path.segments[0].ident.span()),
+            "throws" => self.throws = true,
+            "map_err" => self.map_err = true,
+            "newtype" => self.newtype = true,
+            "no_try_from" => self.no_try_from = true,
+            "copy" => self.copy = true,
+            "sorted" => self.sorted = true,
+            "io_with_context" => self.io_with_context = true,
+            "context" => self.context = true,
+            "skip" => return Err(meta.error(
+                "skip stands alone, `#[errorset(skip)]`, and needs the scope's `default_errorset`",
+            )),
+            "name" => self.name = Some(parse_meta_value(meta)?),
+            "vis" => self.vis = Some(parse_meta_value(meta)?),
+            "in" => self.within = Some(syn::Path::parse_mod_style(meta.input)?),
+            "derive" => meta.parse_nested_meta(|derive| {
+                self.derives.push(derive.path);
+                Ok(())
+            })?,
+            "__parent" => {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                self.parents.push(content.parse()?);
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    // A member, or the members of a `map`, `preset` or `via` group, with the `default` and
+    // `cfg(..)` leading it
+    fn parse_members(&mut self, meta: syn::meta::ParseNestedMeta) -> syn::Result<()> {
+        // Members only take a type, `Read = IoError`, otherwise this is a misspelled option
+        let is_variant = meta
+            .path
+            .get_ident()
+            .is_some_and(|ident| ident.to_string().starts_with(char::is_uppercase));
+        if meta.input.peek(syn::Token![=]) && !is_variant {
+            return Err(meta.error(
+                "unknown errorset option, the options taking a value are `name` and `vis`",
+            ));
+        }
+        // Members are type names, a lowercase one is a misspelled flag
+        if let Some(ident) = meta.path.get_ident() {
+            let ident = ident.to_string();
+            if !is_variant && !["default", "cfg", "via", "map", "dyn"].contains(&ident.as_str()) {
+                let message = match closest_name(&ident, ERRORSET_FLAGS) {
+                    Some(flag) => {
+                        format!("unknown errorset option `{ident}`, did you mean `{flag}`?")
+                    }
+                    None => format!("unknown errorset option `{ident}`"),
+                };
+                return Err(meta.error(message));
+            }
+        }
+        // `default Member`, the member the `Default` impl builds
+        let default = meta.path.is_ident("default");
+        let lead = if default {
+            meta.input.parse::<syn::Path>()?
+        } else {
+            meta.path.clone()
+        };
+        // `cfg(predicate) Member`, the member follows the predicate
+        let (cfg, path) = if lead.is_ident("cfg") {
+            let predicate;
+            syn::parenthesized!(predicate in meta.input);
+            let predicate: TokenStream2 = predicate.parse()?;
+            (Some(predicate), meta.input.parse::<syn::Path>()?)
+        } else {
+            (None, lead)
+        };
+        let grouped = path.is_ident("via")
+            || path.is_ident("map")
+            || (path.segments.len() == 2 && path.segments[0].ident == "preset");
+        if default && grouped {
+            return Err(meta.error("default takes a single member, `default Unknown : \"...\"`"));
+        }
+        if path.is_ident("via") {
+            let content;
+            syn::parenthesized!(content in meta.input);
+            let vias = content.parse_terminated(ViaConversion::parse, syn::Token![,])?;
+            for mut via in vias {
+                via.cfg.clone_from(&cfg);
+                self.vias.push(via);
+            }
+            return Ok(());
+        }
+        if path.is_ident("map") {
+            let content;
+            syn::parenthesized!(content in meta.input);
+            for mut member in parse_member_map(&content)? {
+                member.cfg.clone_from(&cfg);
+                self.members.push(member);
+            }
+            return Ok(());
+        }
+        // `preset::parse`, curated std members, or `preset::Utf8Error` for one of them
+        if path.segments.len() == 2 && path.segments[0].ident == "preset" {
+            let name = &path.segments[1].ident;
+            let Some(presets) = preset_members(&name.to_string()) else {
+                return Err(syn::Error::new(name.span(), "unknown errorset preset"));
+            };
+            let mut annotations = ErrorMember::new(name.clone(), parse_quote!(()));
+            annotations.cfg = cfg;
+            if meta.input.peek(syn::token::Paren) {
+                meta.parse_nested_meta(|annotation| annotations.parse_annotation(annotation))?;
+            }
+            for (variant, ty) in presets {
+                let mut member = annotations.clone();
+                member.variant = Ident::new(variant, name.span());
+                member.ty = ty;
+                self.members.push(member);
+            }
+            return Ok(());
+        }
+        let renamed = meta.input.peek(syn::Token![=]);
+        let mut member = member_of_path(&path, &meta, renamed)?;
+        member.cfg = cfg;
+        member.default = default;
+        // Fields of an inline variant, `Member { field: Type } : "message"`
+        let fields = if meta.input.peek(syn::token::Brace) {
+            Some(meta.input.parse::<syn::FieldsNamed>()?)
+        } else {
+            None
+        };
+        if meta.input.peek(syn::token::Paren) {
+            meta.parse_nested_meta(|annotation| member.parse_annotation(annotation))?;
+        }
+        if meta.input.peek(syn::Token![:]) {
+            if renamed {
+                return Err(meta.error("inline variants wrap no type, drop the `= Type`"));
+            }
+            meta.input.parse::<syn::Token![:]>()?;
+            let message = meta.input.parse()?;
+            member.inline = Some(InlineVariant { fields, message });
+        } else if fields.is_some() {
+            return Err(meta.error("inline variants need a message, `Member { .. } : \"...\"`"));
+        }
+        self.members.push(member);
+        Ok(())
+    }
+}
+
+// The member named by the path of an entry, and the type following it when renamed
+fn member_of_path(
+    path: &syn::Path,
+    meta: &syn::meta::ParseNestedMeta,
+    renamed: bool,
+) -> syn::Result<ErrorMember> {
+    Ok(match path.segments.len() {
+        // `Read = IoError`, the type under a variant name of its own
+        1 if renamed => {
+            meta.input.parse::<syn::Token![=]>()?;
+            let ty = syn::Path::parse_mod_style(meta.input)?;
+            ErrorMember::new(path.segments[0].ident.clone(), parse_quote!(#ty))
+        }
+        // `dyn Diagnostic`, any error implementing the trait, boxed
+        1 if path.is_ident("dyn") => {
+            let dyn_trait = syn::Path::parse_mod_style(meta.input)?;
+            let mut member = ErrorMember::new(
+                Ident::new("Dyn", path.segments[0].ident.span()),
+                parse_quote!(::std::boxed::Box<dyn #dyn_trait + Send + Sync>),
+            );
+            member.dyn_trait = Some(dyn_trait);
+            member
+        }
+        // `io::Error` itself, as the plain io member
+        2 if path.segments[0].ident == "io" && path.segments[1].ident == "Error" => {
+            let ident = &path.segments[1].ident;
+            ErrorMember::new(
+                Ident::new("IoError", ident.span()),
+                parse_quote!(::std::io::Error),
+            )
+        }
+        // `io::NotFound`, an io::Error of that ErrorKind
+        2 if path.segments[0].ident == "io" => {
+            let kind = path.segments[1].ident.clone();
+            let name = kind.to_string();
+            if !IO_ERROR_KINDS.contains(&name.as_str()) {
+                let message = match closest_name(&name, IO_ERROR_KINDS) {
+                    Some(known) => {
+                        format!("`{name}` is not a std::io::ErrorKind, did you mean `io::{known}`?")
+                    }
+                    None => format!("`{name}` is not a std::io::ErrorKind"),
+                };
+                return Err(syn::Error::new(kind.span(), message));
+            }
+            let mut member = ErrorMember::new(
+                Ident::new(&format!("Io{kind}"), kind.span()),
+                parse_quote!(::std::io::Error),
+            );
+            member.route = Some(Route::IoKind(kind));
+            member
+        }
+        _ => {
+            let Some(ident) = path.get_ident() else {
+                let name = &path.segments.last().unwrap().ident;
+                return Err(syn::Error::new_spanned(
+                    path,
+                    format!(
+                        "errorset members are named by a single type name, bring the type \
+                        in with `use`, or name its variant, `{name} = {}`",
+                        quote!(#path).to_string().replace(' ', "")
+                    ),
+                ));
+            };
+            if ident == "IoOther" {
+                let mut member = ErrorMember::new(ident.clone(), parse_quote!(::std::io::Error));
+                member.route = Some(Route::IoOther);
+                member
+            } else {
+                ErrorMember::from(ident.clone())
+            }
+        }
+    })
+}
+
+// The members of `map(Type => { Pattern => Variant, .., _ => Variant })`, each a variant
+// holding the type. Arms routing to the same variant are joined into one or-pattern.
+pub(crate) fn parse_member_map(input: ParseStream) -> syn::Result<Vec<ErrorMember>> {
+    let ty: Type = input.parse()?;
+    input.parse::<syn::Token![=>]>()?;
+    let arms;
+    syn::braced!(arms in input);
+    let mut members: Vec<ErrorMember> = Vec::new();
+    while !arms.is_empty() {
+        let pattern = qualify_pattern(Pat::parse_multi_with_leading_vert(&arms)?, &ty);
+        arms.parse::<syn::Token![=>]>()?;
+        let variant: Ident = arms.parse()?;
+        let mut annotations = ErrorMember::new(variant.clone(), ty.clone());
+        if arms.peek(syn::token::Paren) {
+            let content;
+            syn::parenthesized!(content in arms);
+            let parser = syn::meta::parser(|meta| annotations.parse_annotation(meta));
+            syn::parse::Parser::parse2(parser, content.parse()?)?;
+        }
+        let pattern = match pattern {
+            Pat::Wild(_) => None,
+            pattern => Some(pattern),
+        };
+        match members.iter_mut().find(|member| member.variant == variant) {
+            // Once a variant takes the rest, its patterns are moot
+            Some(member) => {
+                member.route = match (member.route.take(), pattern) {
+                    (Some(Route::Map(Some(first))), Some(pattern)) => {
+                        Some(Route::Map(Some(parse_quote!(#first | #pattern))))
+                    }
+                    _ => Some(Route::Map(None)),
+                }
+            }
+            None => {
+                annotations.route = Some(Route::Map(pattern));
+                members.push(annotations);
+            }
+        }
+        if arms.is_empty() {
+            break;
+        }
+        arms.parse::<syn::Token![,]>()?;
+    }
+    Ok(members)
+}
+
+// Paths in a `map` pattern are relative to the type, `RowNotFound` is `sqlx::Error::RowNotFound`
+pub(crate) fn qualify_pattern(pattern: Pat, ty: &Type) -> Pat {
+    let qualify = |path: &mut syn::Path| {
+        if let Some(ident) = path.get_ident().cloned() {
+            *path = parse_quote!(#ty::#ident);
+        }
+    };
+    match pattern {
+        // A lone capitalized ident is a unit variant, rather than a binding
+        Pat::Ident(binding)
+            if binding.subpat.is_none()
+                && binding.by_ref.is_none()
+                && binding.mutability.is_none()
+                && binding.ident.to_string().starts_with(char::is_uppercase) =>
+        {
+            let ident = binding.ident;
+            parse_quote!(#ty::#ident)
+        }
+        Pat::Path(mut path) => {
+            qualify(&mut path.path);
+            Pat::Path(path)
+        }
+        Pat::TupleStruct(mut tuple) => {
+            qualify(&mut tuple.path);
+            Pat::TupleStruct(tuple)
+        }
+        Pat::Struct(mut strukt) => {
+            qualify(&mut strukt.path);
+            Pat::Struct(strukt)
+        }
+        Pat::Or(mut or) => {
+            or.cases = or
+                .cases
+                .into_iter()
+                .map(|case| qualify_pattern(case, ty))
+                .collect();
+            Pat::Or(or)
+        }
+        Pat::Paren(mut paren) => {
+            *paren.pat = qualify_pattern(*paren.pat, ty);
+            Pat::Paren(paren)
+        }
+        pattern => pattern,
+    }
+}
+
+// Members of the std errors most functions need, by preset or by member name
+pub(crate) fn preset_members(name: &str) -> Option<Vec<(&'static str, Type)>> {
+    let presets: [(&str, &str, Type); 10] = [
+        ("io", "IoError", parse_quote!(::std::io::Error)),
+        ("fmt", "FmtError", parse_quote!(::core::fmt::Error)),
+        (
+            "parse",
+            "ParseIntError",
+            parse_quote!(::core::num::ParseIntError),
+        ),
+        (
+            "parse",
+            "ParseFloatError",
+            parse_quote!(::core::num::ParseFloatError),
+        ),
+        (
+            "parse",
+            "ParseBoolError",
+            parse_quote!(::core::str::ParseBoolError),
+        ),
+        ("parse", "Utf8Error", parse_quote!(::core::str::Utf8Error)),
+        (
+            "parse",
+            "FromUtf8Error",
+            parse_quote!(::std::string::FromUtf8Error),
+        ),
+        ("env", "VarError", parse_quote!(::std::env::VarError)),
+        (
+            "net",
+            "AddrParseError",
+            parse_quote!(::std::net::AddrParseError),
+        ),
+        (
+            "time",
+            "SystemTimeError",
+            parse_quote!(::std::time::SystemTimeError),
+        ),
+    ];
+    let members: Vec<_> = presets
+        .into_iter()
+        .filter(|(preset, variant, _)| *preset == name || *variant == name)
+        .map(|(_, variant, ty)| (variant, ty))
+        .collect();
+    (!members.is_empty()).then_some(members)
+}
+
+// The `Io` member of io_with_context, an io::Error with the path and operation that failed
+pub(crate) fn io_with_context_member() -> ErrorMember {
+    let mut member = ErrorMember::from(Ident::new("Io", proc_macro2::Span::call_site()));
+    member.inline = Some(InlineVariant {
+        fields: Some(parse_quote!({
+            source: ::std::io::Error,
+            path: ::std::path::PathBuf,
+            op: &'static str,
+        })),
+        message: parse_quote!("failed to {op} {path:?}: {source}"),
+    });
+    member
+}
+
+// The `Context` member of the context arg, a message wrapping another error of the enum
+pub(crate) fn context_member() -> ErrorMember {
+    let mut member = ErrorMember::from(Ident::new("Context", proc_macro2::Span::call_site()));
+    member.inline = Some(InlineVariant {
+        fields: Some(parse_quote!({
+            msg: ::std::borrow::Cow<'static, str>,
+            source: ::std::boxed::Box<Self>,
+        })),
+        message: parse_quote!("{msg}"),
+    });
+    member
+}
@@ -0,0 +1,36 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use crate::{name_bail_macros, EnumSpec};
+
+// `foo_error_bail!` and `foo_error_ensure!`. Converted into the enum first, so the error must
+// be one of its members, and then into the function's error with `From`, which may be wider.
+pub(crate) fn bail_macros(spec: &EnumSpec) -> TokenStream2 {
+    if !spec.config.bail_macros {
+        return TokenStream2::new();
+    }
+    let enum_ident = spec.ident;
+    let (bail_ident, ensure_ident) = name_bail_macros(enum_ident);
+    quote! {
+        #[allow(unused_macros)]
+        macro_rules! #bail_ident {
+            ($error:expr $(,)?) => {
+                return ::core::result::Result::Err(::core::convert::From::from(
+                    <#enum_ident as ::core::convert::From<_>>::from($error),
+                ))
+            };
+        }
+        #[allow(unused_macros)]
+        macro_rules! #ensure_ident {
+            ($condition:expr, $error:expr $(,)?) => {
+                if !$condition {
+                    return ::core::result::Result::Err(::core::convert::From::from(
+                        <#enum_ident as ::core::convert::From<_>>::from($error),
+                    ));
+                }
+            };
+        }
+        #[allow(unused_imports)]
+        pub(crate) use {#bail_ident, #ensure_ident};
+    }
+}
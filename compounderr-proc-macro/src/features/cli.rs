@@ -0,0 +1,56 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use crate::EnumSpec;
+
+// Exit codes, hints and user messages for command line apps. Matches on the kind, so the
+// same for the enum and the opaque struct.
+pub(crate) fn cli_reporting(spec: &EnumSpec) -> TokenStream2 {
+    if !spec.config.cli {
+        return TokenStream2::new();
+    }
+    let enum_ident = spec.ident;
+    let kind_ident = spec.kind_ident();
+    let variants = spec.variants();
+    let cfgs = spec.cfgs();
+    let exit_codes = spec.members.iter().map(|member| match &member.exit_code {
+        Some(exit_code) => quote!(#exit_code),
+        None => quote!(1),
+    });
+    let hints = spec.members.iter().map(|member| match &member.hint {
+        Some(hint) => quote!(Some(#hint)),
+        None => quote!(None),
+    });
+    quote! {
+        impl #enum_ident {
+            /// Exit code of the process for this error, members are annotated `exit = N`,
+            /// and default to 1.
+            pub fn exit_code(&self) -> u8 {
+                match self.kind() {
+                    #(#cfgs #kind_ident::#variants => #exit_codes,)*
+                }
+            }
+
+            /// How the user may fix the error, members are annotated `hint = "..."`.
+            pub fn hint(&self) -> Option<&'static str> {
+                match self.kind() {
+                    #(#cfgs #kind_ident::#variants => #hints,)*
+                }
+            }
+
+            /// Short message for the end user, the error and the hint on a second line.
+            pub fn user_message(&self) -> String {
+                match self.hint() {
+                    Some(hint) => format!("error: {self}\nhint: {hint}"),
+                    None => format!("error: {self}"),
+                }
+            }
+        }
+
+        impl From<#enum_ident> for ::std::process::ExitCode {
+            fn from(error: #enum_ident) -> Self {
+                ::std::process::ExitCode::from(error.exit_code())
+            }
+        }
+    }
+}
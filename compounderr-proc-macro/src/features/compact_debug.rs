@@ -0,0 +1,25 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use crate::EnumSpec;
+
+// Debug printing the variant name and the Display of the error, `IoError: not found`
+pub(crate) fn compact_debug(spec: &EnumSpec) -> TokenStream2 {
+    if !spec.config.compact_debug {
+        return TokenStream2::new();
+    }
+    let enum_ident = spec.ident;
+    let variants = spec.variants();
+    let cfgs = spec.cfgs();
+    let names = variants.iter().map(|variant| variant.to_string());
+    quote! {
+        impl ::core::fmt::Debug for #enum_ident {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                let variant = match *self {
+                    #(#cfgs #enum_ident::#variants { .. } => #names,)*
+                };
+                write!(f, "{variant}: {self}")
+            }
+        }
+    }
+}
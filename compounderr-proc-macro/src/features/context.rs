@@ -0,0 +1,61 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use crate::{name_error_context, EnumSpec};
+
+// The `{Enum}Context` extension trait, wrapping errors in the `Context` variant. The opaque
+// struct has no room for the message, the error is kept as it is there.
+pub(crate) fn context_ext(spec: &EnumSpec) -> TokenStream2 {
+    let config = spec.config;
+    if !config.context {
+        return TokenStream2::new();
+    }
+    let enum_ident = spec.ident;
+    let vis = &config.vis;
+    let context_ident = name_error_context(enum_ident);
+    let wrap = quote! {
+        #enum_ident::Context {
+            msg: msg().into(),
+            source: ::std::boxed::Box::new(source.into()),
+        }
+    };
+    let wrap = match config.opaque_gates() {
+        Some((full_gate, opaque_gate)) => quote! {{
+            #full_gate
+            let wrapped = #wrap;
+            #opaque_gate
+            let wrapped = { let _ = msg; source.into() };
+            wrapped
+        }},
+        None => wrap,
+    };
+    quote! {
+        /// Attaches a message to errors converting into
+        #[doc = concat!("[`", stringify!(#enum_ident), "`],")]
+        /// wrapping them in its `Context` variant.
+        #vis trait #context_ident<T> {
+            /// Wraps the error with a static message.
+            fn ctx(self, msg: &'static str) -> ::core::result::Result<T, #enum_ident>;
+
+            /// Wraps the error with a message, only built on error.
+            fn with_ctx<M, F>(self, msg: F) -> ::core::result::Result<T, #enum_ident>
+            where
+                M: Into<::std::borrow::Cow<'static, str>>,
+                F: FnOnce() -> M;
+        }
+
+        impl<T, E: Into<#enum_ident>> #context_ident<T> for ::core::result::Result<T, E> {
+            fn ctx(self, msg: &'static str) -> ::core::result::Result<T, #enum_ident> {
+                self.with_ctx(|| msg)
+            }
+
+            fn with_ctx<M, F>(self, msg: F) -> ::core::result::Result<T, #enum_ident>
+            where
+                M: Into<::std::borrow::Cow<'static, str>>,
+                F: FnOnce() -> M,
+            {
+                self.map_err(|source| #wrap)
+            }
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use crate::{name_ffi_code, EnumSpec, ErrorMember};
+
+// A C ABI error code enum, with `to_ffi()`. Numbered like the kinds, so C callers see the
+// same codes.
+pub(crate) fn ffi_code(spec: &EnumSpec) -> TokenStream2 {
+    let config = spec.config;
+    if !config.ffi {
+        return TokenStream2::new();
+    }
+    let enum_ident = spec.ident;
+    let vis = &config.vis;
+    let kind_ident = spec.kind_ident();
+    let variants = spec.variants();
+    let cfgs = spec.cfgs();
+    let ffi_ident = name_ffi_code(enum_ident);
+    let ffi_repr = match &config.repr {
+        Some(repr) => quote!(#[repr(#repr)]),
+        None => quote!(#[repr(C)]),
+    };
+    let discriminants = spec.members.iter().map(ErrorMember::discriminant);
+    quote! {
+        /// C ABI error code of
+        #[doc = concat!("[`", stringify!(#enum_ident), "`],")]
+        /// one per variant.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #ffi_repr
+        #vis enum #ffi_ident {
+            #(#cfgs #variants #discriminants),*
+        }
+
+        impl #enum_ident {
+            /// The error code to hand over a C ABI.
+            pub fn to_ffi(&self) -> #ffi_ident {
+                match self.kind() {
+                    #(#cfgs #kind_ident::#variants => #ffi_ident::#variants,)*
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use crate::{pascal_to_snake, EnumSpec};
+
+// `async_graphql::ErrorExtensions`. Matches on the kind too, codes default to the variant
+// name in SCREAMING_SNAKE_CASE.
+pub(crate) fn graphql_extensions(spec: &EnumSpec) -> TokenStream2 {
+    if !spec.config.graphql {
+        return TokenStream2::new();
+    }
+    let enum_ident = spec.ident;
+    let kind_ident = spec.kind_ident();
+    let variants = spec.variants();
+    let cfgs = spec.cfgs();
+    let codes = spec
+        .members
+        .iter()
+        .map(|member| match &member.graphql_code {
+            Some(code) => code.value(),
+            None => pascal_to_snake(&member.variant.to_string()).to_ascii_uppercase(),
+        });
+    let statuses = spec.members.iter().map(|member| match &member.http_status {
+        Some(status) => quote!(Some(#status)),
+        None => quote!(None),
+    });
+    quote! {
+        impl ::async_graphql::ErrorExtensions for #enum_ident {
+            fn extend(&self) -> ::async_graphql::Error {
+                let (code, status): (&'static str, Option<u16>) = match self.kind() {
+                    #(#cfgs #kind_ident::#variants => (#codes, #statuses),)*
+                };
+                ::async_graphql::ErrorExtensions::extend_with(
+                    ::async_graphql::Error::new(self.to_string()),
+                    |_, extensions| {
+                        extensions.set("code", code);
+                        if let Some(status) = status {
+                            extensions.set("status", status);
+                        }
+                    },
+                )
+            }
+        }
+    }
+}
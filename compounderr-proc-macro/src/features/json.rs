@@ -0,0 +1,31 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use crate::EnumSpec;
+
+// `to_json()`. Only Display is needed of the members and their sources, none has to be
+// Serialize.
+pub(crate) fn json(spec: &EnumSpec) -> TokenStream2 {
+    if !spec.config.json {
+        return TokenStream2::new();
+    }
+    let enum_ident = spec.ident;
+    let composed_trait = spec.composed_trait_in_scope();
+    quote! {
+        impl #enum_ident {
+            /// The error as JSON, `{"kind", "code", "message", "source_chain"}`, for
+            /// structured logs. Sources are rendered with their Display, outermost first.
+            pub fn to_json(&self) -> ::serde_json::Value {
+                #composed_trait
+                let source_chain: Vec<String> =
+                    self.chain().skip(1).map(|cause| cause.to_string()).collect();
+                ::serde_json::json!({
+                    "kind": format!("{:?}", self.kind()),
+                    "code": self.kind() as u16,
+                    "message": self.to_string(),
+                    "source_chain": source_chain,
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use crate::EnumSpec;
+
+// Equality, hashing and ordering comparing only the kind. The opaque struct only holds the
+// kind, and already derives these.
+pub(crate) fn kind_eq(spec: &EnumSpec) -> TokenStream2 {
+    let config = spec.config;
+    if !config.kind_eq {
+        return TokenStream2::new();
+    }
+    let enum_ident = spec.ident;
+    let derived = ["PartialEq", "Eq", "Hash", "PartialOrd", "Ord"];
+    if let Some(path) = config
+        .derives
+        .iter()
+        .find(|path| derived.iter().any(|name| path.is_ident(name)))
+    {
+        return syn::Error::new_spanned(
+            path,
+            "kind_eq already implements this by comparing kinds, drop the derive",
+        )
+        .to_compile_error();
+    }
+    quote! {
+        impl ::core::cmp::PartialEq for #enum_ident {
+            fn eq(&self, other: &Self) -> bool {
+                self.kind() == other.kind()
+            }
+        }
+
+        impl ::core::cmp::Eq for #enum_ident {}
+
+        impl ::core::hash::Hash for #enum_ident {
+            fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+                self.kind().hash(state)
+            }
+        }
+
+        impl ::core::cmp::PartialOrd for #enum_ident {
+            fn partial_cmp(&self, other: &Self) -> Option<::core::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl ::core::cmp::Ord for #enum_ident {
+            fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                self.kind().cmp(&other.kind())
+            }
+        }
+    }
+}
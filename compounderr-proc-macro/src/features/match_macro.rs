@@ -0,0 +1,57 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+
+use crate::{name_match_macro, pascal_to_snake, EnumSpec, InlineVariant};
+
+// `match_foo_error!`, a muncher turning each `snake_name(pattern) => expr` arm into the
+// variant's pattern, the enum being named as in scope where the macro is used
+pub(crate) fn match_macro(spec: &EnumSpec) -> TokenStream2 {
+    let config = spec.config;
+    if !config.match_macro {
+        return TokenStream2::new();
+    }
+    let enum_ident = spec.ident;
+    let macro_ident = name_match_macro(enum_ident);
+    let rules = spec.members.iter().map(|member| {
+        let variant = &member.variant;
+        let name = format_ident!("{}", pascal_to_snake(&variant.to_string()));
+        let munch = |arm: TokenStream2, pattern: TokenStream2| {
+            quote! {
+                (@arms $error:tt [$($done:tt)*] #arm => $body:expr $(, $($rest:tt)*)?) => {
+                    #macro_ident!(@arms $error [$($done)* #enum_ident::#variant #pattern => $body,] $($($rest)*)?)
+                };
+            }
+        };
+        let payload = match &member.inline {
+            None => munch(
+                quote!(#name($pattern:pat)),
+                config.member_fields(quote!($pattern)),
+            ),
+            Some(InlineVariant {
+                fields: Some(_), ..
+            }) => munch(quote!(#name { $($fields:tt)* }), quote!({ $($fields)* })),
+            Some(InlineVariant { fields: None, .. }) => TokenStream2::new(),
+        };
+        let bare = munch(quote!(#name), quote!({ .. }));
+        quote!(#payload #bare)
+    });
+    quote! {
+        #[allow(unused_macros)]
+        macro_rules! #macro_ident {
+            (@arms ($error:expr) [$($done:tt)*]) => {
+                match $error {
+                    $($done)*
+                }
+            };
+            #(#rules)*
+            (@arms $error:tt [$($done:tt)*] _ => $body:expr $(,)?) => {
+                #macro_ident!(@arms $error [$($done)* _ => $body,])
+            };
+            ($error:expr, $($arms:tt)*) => {
+                #macro_ident!(@arms ($error) [] $($arms)*)
+            };
+        }
+        #[allow(unused_imports)]
+        pub(crate) use #macro_ident;
+    }
+}
@@ -0,0 +1,37 @@
+// Optional helpers of the composed enums, each rendered from the `EnumSpec` when its option
+// is set, and empty otherwise. Most go through `kind()`, serving the opaque struct as well.
+mod bail;
+mod cli;
+mod compact_debug;
+mod context;
+mod ffi;
+mod graphql;
+mod json;
+mod kind_eq;
+mod match_macro;
+mod otel;
+mod problem;
+mod report;
+mod severity;
+mod terrors;
+mod variant_names;
+mod visitor;
+mod widen;
+
+pub(crate) use bail::bail_macros;
+pub(crate) use cli::cli_reporting;
+pub(crate) use compact_debug::compact_debug;
+pub(crate) use context::context_ext;
+pub(crate) use ffi::ffi_code;
+pub(crate) use graphql::graphql_extensions;
+pub(crate) use json::json;
+pub(crate) use kind_eq::kind_eq;
+pub(crate) use match_macro::match_macro;
+pub(crate) use otel::otel;
+pub(crate) use problem::problem;
+pub(crate) use report::report;
+pub(crate) use severity::{severity, severity_check};
+pub(crate) use terrors::terrors_conversions;
+pub(crate) use variant_names::variant_names;
+pub(crate) use visitor::visitor;
+pub(crate) use widen::widen;
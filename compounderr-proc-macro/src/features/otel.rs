@@ -0,0 +1,26 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use crate::EnumSpec;
+
+// `otel_attributes()`. Keyed after the `error.type` semantic convention, the others under
+// the same namespace.
+pub(crate) fn otel(spec: &EnumSpec) -> TokenStream2 {
+    if !spec.config.otel {
+        return TokenStream2::new();
+    }
+    let enum_ident = spec.ident;
+    quote! {
+        impl #enum_ident {
+            /// The kind, code and retryability of the error as OpenTelemetry attributes,
+            /// `error.type`, `error.code` and `error.retryable`, for spans and events.
+            pub fn otel_attributes(&self) -> [::opentelemetry::KeyValue; 3] {
+                [
+                    ::opentelemetry::KeyValue::new("error.type", format!("{:?}", self.kind())),
+                    ::opentelemetry::KeyValue::new("error.code", i64::from(self.kind() as u16)),
+                    ::opentelemetry::KeyValue::new("error.retryable", self.is_retryable()),
+                ]
+            }
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use crate::{pascal_to_snake, EnumSpec};
+
+// `to_problem()`, as the `ProblemDetails` declared with `problem_details_type!`. Untyped
+// problems are `about:blank`, titled after the variant, with a 500 status.
+pub(crate) fn problem(spec: &EnumSpec) -> TokenStream2 {
+    let Some(types) = &spec.config.problem else {
+        return TokenStream2::new();
+    };
+    let enum_ident = spec.ident;
+    let kind_ident = spec.kind_ident();
+    let variants = spec.variants();
+    let cfgs = spec.cfgs();
+    let problem_types = spec
+        .members
+        .iter()
+        .map(|member| match &member.problem_type {
+            Some(problem_type) => problem_type.value(),
+            None => "about:blank".to_string(),
+        });
+    let titles = spec.members.iter().map(|member| match &member.title {
+        Some(title) => title.value(),
+        None => {
+            let words = pascal_to_snake(&member.variant.to_string()).replace('_', " ");
+            let mut chars = words.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        }
+    });
+    let statuses = spec.members.iter().map(|member| match &member.http_status {
+        Some(status) => quote!(#status),
+        None => quote!(500),
+    });
+    quote! {
+        impl #enum_ident {
+            /// The error as RFC 7807 problem details, for HTTP API responses. The
+            /// detail is its Display, the instance is left for the handler to set.
+            pub fn to_problem(&self) -> #types::ProblemDetails {
+                let (r#type, title, status): (&'static str, &'static str, u16) =
+                    match self.kind() {
+                        #(#cfgs #kind_ident::#variants => (#problem_types, #titles, #statuses),)*
+                    };
+                #types::ProblemDetails {
+                    r#type,
+                    title,
+                    status,
+                    detail: self.to_string(),
+                    instance: None,
+                }
+            }
+        }
+    }
+}
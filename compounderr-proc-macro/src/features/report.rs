@@ -0,0 +1,29 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use crate::EnumSpec;
+
+// `report()`. Through `kind()` and `chain()`, so the same for the enum and the opaque struct.
+pub(crate) fn report(spec: &EnumSpec) -> TokenStream2 {
+    if !spec.config.report {
+        return TokenStream2::new();
+    }
+    let enum_ident = spec.ident;
+    let composed_trait = spec.composed_trait_in_scope();
+    quote! {
+        impl #enum_ident {
+            /// Renders the variant name and the error, then each of its sources on a line
+            /// of its own, indented one step deeper than the one it caused.
+            pub fn report(&self) -> String {
+                #composed_trait
+                use ::core::fmt::Write as _;
+                let mut report = format!("{:?}: {}", self.kind(), self);
+                for (depth, cause) in self.chain().skip(1).enumerate() {
+                    let indent = 2 * (depth + 1);
+                    let _ = write!(report, "\n{:indent$}caused by: {cause}", "");
+                }
+                report
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::Ident;
+
+use crate::{EnumSpec, Severity};
+
+// `severity()` on the enum, or its opaque struct, and on the kind the former goes through.
+// Only with the `severity` option, see `ComposeConfig::severity_type`.
+pub(crate) fn severity(spec: &EnumSpec) -> (TokenStream2, TokenStream2) {
+    let Some(severity_type) = spec.config.severity_type() else {
+        return (TokenStream2::new(), TokenStream2::new());
+    };
+    let kind_ident = spec.kind_ident();
+    let variants = spec.variants();
+    let cfgs = spec.cfgs();
+    let severities = spec.members.iter().map(|member| {
+        let severity = match member.severity {
+            Severity::Warning => "Warning",
+            Severity::Error => "Error",
+            Severity::Fatal => "Fatal",
+        };
+        Ident::new(severity, member.variant.span())
+    });
+    let method = quote! {
+        /// The severity of the error held, members are annotated `warning` or `fatal`,
+        /// and default to `Error`.
+        pub fn severity(&self) -> #severity_type {
+            self.kind().severity()
+        }
+    };
+    let kind_method = quote! {
+        /// The severity of errors of this kind, members are annotated `warning` or `fatal`,
+        /// and default to `Error`.
+        pub fn severity(self) -> #severity_type {
+            match self {
+                #(#cfgs #kind_ident::#variants => #severity_type::#severities,)*
+            }
+        }
+    };
+    (method, kind_method)
+}
+
+// Members annotated `warning` or `fatal` need the shared `Severity`, which the macro can't
+// tell is declared. Without the `severity` option, say how, rather than failing to find it.
+pub(crate) fn severity_check(spec: &EnumSpec) -> TokenStream2 {
+    if spec.config.severity.is_some() {
+        return TokenStream2::new();
+    }
+    let errors = spec
+        .members
+        .iter()
+        .filter(|member| member.severity != Severity::Error)
+        .map(|member| {
+            let annotation = match member.severity {
+                Severity::Warning => "warning",
+                _ => "fatal",
+            };
+            syn::Error::new(
+                member.variant.span(),
+                format!(
+                    "`{annotation}` needs the shared Severity type, declare it with \
+                    `composerr::severity_type!()` and enable it with `#[compose_errors(severity)]`, \
+                    or `severity = \"path::to::module\"` where it is declared elsewhere"
+                ),
+            )
+            .to_compile_error()
+        });
+    quote!(#(#errors)*)
+}
@@ -0,0 +1,73 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::Type;
+
+use crate::EnumSpec;
+
+// Conversions to and from the structural `terrors::OneOf<(A, B, ..)>` of the members.
+// Going back, the OneOf is narrowed down one member at a time.
+pub(crate) fn terrors_conversions(spec: &EnumSpec) -> TokenStream2 {
+    let config = spec.config;
+    if !config.terrors {
+        return TokenStream2::new();
+    }
+    let enum_ident = spec.ident;
+    let error_set = spec.members;
+    if let Some(member) = error_set.iter().find(|member| member.cfg.is_some()) {
+        return syn::Error::new(
+            member.variant.span(),
+            "terrors conversions can't have cfg members, the OneOf type would change with it",
+        )
+        .to_compile_error();
+    }
+    if let Some(member) = error_set.iter().find(|member| member.inline.is_some()) {
+        return syn::Error::new(
+            member.variant.span(),
+            "terrors conversions can't have inline variants, there is no type to put in the OneOf",
+        )
+        .to_compile_error();
+    }
+    if let Some(member) = error_set
+        .iter()
+        .find(|member| member.route.is_some() || member.shared)
+    {
+        return syn::Error::new(
+            member.variant.span(),
+            "terrors conversions can't have a type under several variants, the OneOf would hold it twice",
+        )
+        .to_compile_error();
+    }
+    let Some((last, narrowed)) = error_set.split_last() else {
+        // Nothing to convert, an empty OneOf can't exist
+        return TokenStream2::new();
+    };
+    let bind_e = config.member_fields(quote!(e));
+    let variants = error_set.iter().map(|member| &member.variant);
+    let types: Vec<&Type> = error_set.iter().map(|member| &member.ty).collect();
+    let narrowed_types = narrowed.iter().map(|member| &member.ty);
+    let last_type = &last.ty;
+
+    quote! {
+        impl From<#enum_ident> for ::terrors::OneOf<(#(#types,)*)> {
+            fn from(value: #enum_ident) -> Self {
+                match value {
+                    #(#enum_ident::#variants #bind_e => ::terrors::OneOf::new(e),)*
+                }
+            }
+        }
+
+        impl From<::terrors::OneOf<(#(#types,)*)>> for #enum_ident {
+            #[track_caller]
+            fn from(value: ::terrors::OneOf<(#(#types,)*)>) -> Self {
+                #(
+                    let value = match value.narrow::<#narrowed_types, _>() {
+                        Ok(e) => return #enum_ident::from(e),
+                        Err(remainder) => remainder,
+                    };
+                )*
+                let last: #last_type = value.take();
+                #enum_ident::from(last)
+            }
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use crate::EnumSpec;
+
+// Variant names as strings on the enum, and iteration over the kinds
+pub(crate) fn variant_names(spec: &EnumSpec) -> (TokenStream2, TokenStream2) {
+    if !spec.config.variant_names {
+        return (TokenStream2::new(), TokenStream2::new());
+    }
+    let enum_ident = spec.ident;
+    let kind_ident = spec.kind_ident();
+    let variants = spec.variants();
+    let cfgs = spec.cfgs();
+    let names: Vec<String> = variants.iter().map(|variant| variant.to_string()).collect();
+    let enum_names = quote! {
+        impl #enum_ident {
+            /// Names of all the variants, in declaration order.
+            pub const VARIANTS: &'static [&'static str] = &[#(#cfgs #names),*];
+
+            /// Name of the variant held.
+            pub fn variant_name(&self) -> &'static str {
+                match *self {
+                    #(#cfgs #enum_ident::#variants { .. } => #names,)*
+                }
+            }
+        }
+    };
+    let kind_names = quote! {
+        impl #kind_ident {
+            /// All the kinds, in declaration order.
+            pub const ALL: &'static [#kind_ident] = &[#(#cfgs #kind_ident::#variants),*];
+
+            /// Iterates over all the kinds, in declaration order.
+            pub fn iter() -> impl Iterator<Item = #kind_ident> {
+                Self::ALL.iter().copied()
+            }
+
+            /// Name of the kind, same as its variant's.
+            pub fn name(&self) -> &'static str {
+                match *self {
+                    #(#cfgs #kind_ident::#variants => #names,)*
+                }
+            }
+        }
+    };
+    (enum_names, kind_names)
+}
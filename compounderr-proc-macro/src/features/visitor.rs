@@ -0,0 +1,71 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::Ident;
+
+use crate::{name_error_visitor, pascal_to_snake, EnumSpec, InlineVariant};
+
+// A `FooErrorVisitor` trait with a method per variant, and `FooError::visit`. Adding a
+// variant breaks every visitor, so handlers can't miss one.
+pub(crate) fn visitor(spec: &EnumSpec) -> TokenStream2 {
+    let config = spec.config;
+    if !config.visitor {
+        return TokenStream2::new();
+    }
+    let enum_ident = spec.ident;
+    let vis = &config.vis;
+    let variants = spec.variants();
+    let cfgs = spec.cfgs();
+    let bind_e = config.member_fields(quote!(e));
+    let visitor_ident = name_error_visitor(enum_ident);
+    let methods: Vec<Ident> = variants
+        .iter()
+        .map(|variant| format_ident!("visit_{}", pascal_to_snake(&variant.to_string())))
+        .collect();
+    let (patterns, (params, args)): (Vec<_>, (Vec<_>, Vec<_>)) = spec
+        .members
+        .iter()
+        .map(|member| match &member.inline {
+            None => {
+                let ty = &member.ty;
+                (bind_e.clone(), (quote!(error: #ty), quote!(e)))
+            }
+            Some(InlineVariant {
+                fields: Some(fields),
+                ..
+            }) => {
+                let names: Vec<&Option<Ident>> =
+                    fields.named.iter().map(|field| &field.ident).collect();
+                let types = fields.named.iter().map(|field| &field.ty);
+                (
+                    quote!({ #(#names),* }),
+                    (quote!(#(#names: #types),*), quote!(#(#names),*)),
+                )
+            }
+            Some(InlineVariant { fields: None, .. }) => {
+                (quote!({ .. }), (TokenStream2::new(), TokenStream2::new()))
+            }
+        })
+        .unzip();
+    quote! {
+        /// Handles each variant of
+        #[doc = concat!("[`", stringify!(#enum_ident), "`]")]
+        /// with a method of its own, see its `visit`.
+        #vis trait #visitor_ident {
+            /// What the handlers return.
+            type Output;
+            #(
+                #cfgs
+                fn #methods(self, #params) -> Self::Output;
+            )*
+        }
+
+        impl #enum_ident {
+            /// Hands the error held over to the visitor method of its variant.
+            pub fn visit<V: #visitor_ident>(self, visitor: V) -> V::Output {
+                match self {
+                    #(#cfgs #enum_ident::#variants #patterns => visitor.#methods(#args),)*
+                }
+            }
+        }
+    }
+}
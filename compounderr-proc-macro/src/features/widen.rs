@@ -0,0 +1,35 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use crate::{name_error_widen, EnumSpec};
+
+// A `FooErrorWiden` trait, with `.widen::<Wider>()` on `Result<T, FooError>`
+pub(crate) fn widen(spec: &EnumSpec) -> TokenStream2 {
+    let config = spec.config;
+    if !config.widen {
+        return TokenStream2::new();
+    }
+    let enum_ident = spec.ident;
+    let vis = &config.vis;
+    let widen_ident = name_error_widen(enum_ident);
+    let track_caller = config.track_caller.then(|| quote!(#[track_caller]));
+    quote! {
+        /// Converts the error of a
+        #[doc = concat!("`Result<T, ", stringify!(#enum_ident), ">`")]
+        /// into a wider error, as `?` would, where `?` isn't used.
+        #vis trait #widen_ident<T> {
+            /// The result with its error converted into `W`, like `.map_err(W::from)`.
+            fn widen<W: From<#enum_ident>>(self) -> ::core::result::Result<T, W>;
+        }
+
+        impl<T> #widen_ident<T> for ::core::result::Result<T, #enum_ident> {
+            #track_caller
+            fn widen<W: From<#enum_ident>>(self) -> ::core::result::Result<T, W> {
+                match self {
+                    Ok(value) => Ok(value),
+                    Err(error) => Err(W::from(error)),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote, Ident, Item, ItemTrait, PathArguments, TraitItem, Type,
+};
+
+use crate::{
+    errorset_args, is_errorset_attr, item_fn_attrs, name_export_macro, name_inherit_macro,
+    ErrorMember, FuncErrors,
+};
+
+// Input of the hidden `__inherit_errorset!` macro
+pub(crate) struct InheritedErrorset {
+    pub(crate) members: TokenStream2,
+    // From a supertrait, its method's enum, and the path from the subtrait to its module
+    pub(crate) parent: Option<(TokenStream2, TokenStream2)>,
+    // `super::method`, or `module::*`
+    pub(crate) reference: TokenStream2,
+    pub(crate) compose_args: TokenStream2,
+    pub(crate) item: Item,
+}
+
+impl Parse for InheritedErrorset {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let members;
+        syn::bracketed!(members in input);
+        let parent = if input.peek(syn::token::Brace) {
+            let (conversion, prefix);
+            syn::braced!(conversion in input);
+            syn::bracketed!(prefix in input);
+            Some((conversion.parse()?, prefix.parse()?))
+        } else {
+            None
+        };
+        let mut reference = TokenStream2::new();
+        while !input.peek(syn::token::Paren) {
+            reference.extend([input.parse::<proc_macro2::TokenTree>()?]);
+        }
+        let compose_args;
+        syn::parenthesized!(compose_args in input);
+        Ok(InheritedErrorset {
+            members: members.parse()?,
+            parent,
+            reference,
+            compose_args: compose_args.parse()?,
+            item: input.parse()?,
+        })
+    }
+}
+
+// Splits a token stream on its top level commas
+pub(crate) fn split_on_commas(tokens: TokenStream2) -> Vec<TokenStream2> {
+    let mut entries = vec![TokenStream2::new()];
+    for tree in tokens {
+        match &tree {
+            proc_macro2::TokenTree::Punct(punct) if punct.as_char() == ',' => {
+                entries.push(TokenStream2::new())
+            }
+            _ => entries.last_mut().unwrap().extend([tree]),
+        }
+    }
+    entries.retain(|entry| !entry.is_empty());
+    entries
+}
+
+// For a trait, a hidden macro with an arm per composed method, carrying its members and the
+// path of its enum. `super::method` references in subtraits are resolved by calling it, see
+// `__inherit_errorset`. It is also imported under the trait's own name, in the macro
+// namespace, so a `use` of the trait brings it along.
+pub(crate) fn inheritable_errorsets(
+    trait_ident: &Ident,
+    functions: &[FuncErrors],
+    enum_type: impl Fn(&FuncErrors) -> Type,
+) -> TokenStream2 {
+    let macro_ident = name_inherit_macro(trait_ident);
+    let arms = functions.iter().map(|func| {
+        let method = &func.name;
+        let members = func.error_set.iter().map(ErrorMember::spec);
+        // Newtypes have no variants to convert from
+        let parent = if func.newtype {
+            TokenStream2::new()
+        } else {
+            let cfgs = &func.cfgs;
+            let ty = enum_type(func);
+            let variants = func.error_set.iter().map(|member| &member.variant);
+            quote!(#(#cfgs)* #ty => #(#variants),*)
+        };
+        quote! {
+            (#method $($child:tt)*) => {
+                ::composerr::__inherit_errorset! { [#(#members),*] {#parent} $($child)* }
+            };
+        }
+    });
+    let message = format!("`{trait_ident}` has no composed method `");
+    quote! {
+        #[doc(hidden)]
+        #[allow(unused_macros)]
+        macro_rules! #macro_ident {
+            #(#arms)*
+            ($method:ident $($child:tt)*) => {
+                ::core::compile_error! {
+                    ::core::concat!(#message, ::core::stringify!($method), "` to inherit an errorset from")
+                }
+            };
+        }
+        #[allow(unused_imports)]
+        pub(crate) use #macro_ident as #trait_ident;
+    }
+}
+
+// The module of a glob errorset entry, `crate::base` of `crate::base::*`
+pub(crate) fn glob_module(entry: &TokenStream2) -> Option<TokenStream2> {
+    let mut tokens: Vec<proc_macro2::TokenTree> = entry.clone().into_iter().collect();
+    let is_glob = matches!(
+        &tokens[..],
+        [.., proc_macro2::TokenTree::Punct(first), proc_macro2::TokenTree::Punct(second), proc_macro2::TokenTree::Punct(star)]
+            if first.as_char() == ':' && second.as_char() == ':' && star.as_char() == '*'
+    );
+    if !is_glob || tokens.len() < 4 {
+        return None;
+    }
+    tokens.truncate(tokens.len() - 3);
+    Some(tokens.into_iter().collect())
+}
+
+// If any errorset imports a module's exports, `module::*`, defers composition of the whole
+// item to the macro left behind by `export_errorset!` in that module. Each glob is resolved
+// in turn, composing again once none is left.
+pub(crate) fn defer_glob_errorset(ast: &Item, compose_args: TokenStream2) -> Option<TokenStream2> {
+    let mut item = ast.clone();
+    let glob = item_fn_attrs(&mut item)
+        .into_iter()
+        .flatten()
+        .filter(|attr| is_errorset_attr(attr))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::List(list) => Some(list.tokens.clone()),
+            _ => None,
+        })
+        .flat_map(split_on_commas)
+        .find_map(|entry| Some((glob_module(&entry)?, entry)))?;
+    let (module, entry) = glob;
+    let macro_ident = name_export_macro();
+    Some(quote! {
+        #module::#macro_ident! { #entry (#compose_args) #ast }
+    })
+}
+
+// If any errorset of the trait references a supertrait method (`super::method`, or
+// `super::Parent::method` with several supertraits), defers composition of the whole
+// trait to the macro left behind by the supertrait's #[compose_errors], imported with it.
+pub(crate) fn defer_inherited_errorset(
+    trait_def: &ItemTrait,
+    compose_args: TokenStream2,
+) -> Option<TokenStream2> {
+    let reference = trait_def
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            TraitItem::Fn(item) => Some(item),
+            _ => None,
+        })
+        .filter_map(|item| errorset_args(&item.attrs))
+        .flat_map(|args| args.inherits)
+        .next()?;
+
+    let supertraits: Vec<&syn::Path> = trait_def
+        .supertraits
+        .iter()
+        .filter_map(|bound| match bound {
+            syn::TypeParamBound::Trait(bound) => Some(&bound.path),
+            _ => None,
+        })
+        .collect();
+    let (parent, method) = match reference.segments.len() {
+        2 => match supertraits.as_slice() {
+            [parent] => ((*parent).clone(), &reference.segments[1].ident),
+            _ => {
+                let error = syn::Error::new_spanned(
+                    &reference,
+                    "use `super::Parent::method` to inherit an errorset when there isn't exactly one supertrait",
+                );
+                return Some(error.to_compile_error());
+            }
+        },
+        3 => {
+            let parent_ident = &reference.segments[1].ident;
+            let parent = supertraits
+                .iter()
+                .find(|path| &path.segments.last().unwrap().ident == parent_ident)
+                .map(|path| (*path).clone())
+                .unwrap_or_else(|| parse_quote!(#parent_ident));
+            (parent, &reference.segments[2].ident)
+        }
+        _ => {
+            let error = syn::Error::new_spanned(
+                &reference,
+                "inherited errorsets are referenced as `super::method` or `super::Parent::method`",
+            );
+            return Some(error.to_compile_error());
+        }
+    };
+
+    // The macro goes by the supertrait's name. The enums it names are relative to the
+    // supertrait's module, reached from here through the path's leading segments.
+    let mut macro_path = parent;
+    macro_path.segments.last_mut().unwrap().arguments = PathArguments::None;
+    let leading_colon = macro_path.leading_colon;
+    let modules = macro_path
+        .segments
+        .iter()
+        .take(macro_path.segments.len() - 1);
+    let prefix = quote!(#leading_colon #(#modules)::*);
+
+    Some(quote! {
+        #macro_path! { #method [#prefix] #reference (#compose_args) #trait_def }
+    })
+}
@@ -1,324 +1,941 @@
 use proc_macro::TokenStream;
-use quote::quote;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned, ToTokens};
 use syn::{
-    parse_macro_input, parse_quote, Attribute, GenericArgument, Ident, ImplItem, Item, ItemFn,
-    ItemImpl, ItemTrait, PathArguments, ReturnType, TraitItem, Type,
+    parse::{Parse, ParseStream},
+    parse_macro_input, parse_quote, Attribute, Block, Ident, ImplItem, Item, ReturnType, Signature,
+    TraitItem, Type,
+};
+
+mod backend;
+#[cfg(feature = "body-analysis")]
+mod body;
+mod catalog;
+mod composed;
+mod config;
+mod errorset;
+mod features;
+mod inherit;
+mod member;
+mod mock;
+mod module;
+mod naming;
+mod newtype;
+mod opaque;
+mod scope;
+mod signature;
+use backend::{BackendKind, EnumSpec};
+use catalog::{catalog_types, compose_error_catalog, compose_scope_index};
+use composed::{compose_error_enum, compose_sibling_conversions};
+use config::{parse_meta_value, ComposeConfig};
+use errorset::{errorset_args, is_errorset_attr, preset_members, ErrorsetArgs};
+use inherit::{
+    defer_glob_errorset, defer_inherited_errorset, glob_module, inheritable_errorsets,
+    split_on_commas, InheritedErrorset,
+};
+use member::{
+    default_impl, mark_shared_members, routed_from_impls, shared_constructors,
+    variant_constructors, ErrorMember, InlineVariant, ParentConversion, Route, Severity,
+    ViaConversion,
+};
+use mock::{compose_trait_mock, is_impl_trait_output};
+use module::{apply_default_errorset, compose_module, is_relative_errorset, unannotated_results};
+use naming::{
+    closest_name, doc_path, name_bail_macros, name_composed_error, name_error_context,
+    name_error_kind, name_error_visitor, name_error_widen, name_export_macro, name_ffi_code,
+    name_inherit_macro, name_match_macro, name_result_alias, name_scope_marker, name_sealed_marker,
+    name_sealed_module, name_trait_mock, name_type_arguments, pascal_to_snake, snake_to_pascal,
+};
+use newtype::compose_newtype_error;
+use opaque::compose_opaque_error;
+use scope::{
+    add_typed_siblings, process_bare_function, process_impl_block, process_trait_def,
+    use_trait_errors,
+};
+use signature::{
+    existing_result_error, generic_error_conflict, has_inferred_error, is_boxed_trait_object,
+    mentions_ident, replace_func_output, result_error_slots, spell_item_result_aliases,
+    substitute_generic_error,
 };
 
 #[proc_macro_attribute]
-pub fn compose_errors(_attrs: TokenStream, input: TokenStream) -> TokenStream {
+pub fn compose_errors(attrs: TokenStream, input: TokenStream) -> TokenStream {
     // Parse the input into a syntax tree
     let mut ast = parse_macro_input!(input as syn::Item);
 
+    // Errorsets inheriting from a supertrait method wait until its members are known
+    if let Item::Trait(trait_def) = &ast {
+        if let Some(deferred) = defer_inherited_errorset(trait_def, attrs.clone().into()) {
+            return TokenStream::from(deferred);
+        }
+    }
+    // And so do errorsets importing the members exported by a module
+    if let Some(deferred) = defer_glob_errorset(&ast, attrs.clone().into()) {
+        return TokenStream::from(deferred);
+    }
+
+    let compose_args: TokenStream2 = attrs.clone().into();
+    let mut config = ComposeConfig::default();
+    let config_parser = syn::meta::parser(|meta| config.parse_arg(meta));
+    parse_macro_input!(attrs with config_parser);
+    if let Err(err) = config.validate() {
+        return err.to_compile_error().into();
+    }
+
+    // A module composes each of its items, nested modules included, with the same arguments
+    if let Item::Mod(module) = &mut ast {
+        return TokenStream::from(compose_module(module, compose_args));
+    }
+
     // Check if the input is a function, trait def or an impl block
-    let (input_scope, functions) = match &mut ast {
-        Item::Trait(trait_def) => process_trait_def(trait_def),
-        Item::Impl(impl_block) => process_impl_block(impl_block),
-        Item::Fn(function) => process_bare_function(function),
-        _ => panic!("This macro can only be used on functions, traits or implementations."),
+    if config.block.is_some() && !matches!(ast, Item::Impl(_)) {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "block only applies to impl blocks, telling apart several of the same type",
+        )
+        .to_compile_error()
+        .into();
+    }
+    spell_item_result_aliases(&mut ast, &config);
+    // Implementations only take the trait's enums, nothing is composed
+    if config.use_trait {
+        let Item::Impl(impl_block) = &mut ast else {
+            return syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "use_trait only applies to implementations of a trait, `impl Trait for Type`",
+            )
+            .to_compile_error()
+            .into();
+        };
+        let errors = use_trait_errors(impl_block, &config);
+        return TokenStream::from(quote!(#errors #ast));
+    }
+    let relative = apply_default_errorset(&mut ast, &config);
+    let unannotated = unannotated_results(&ast);
+    let (input_scope, mut functions) = match &mut ast {
+        Item::Trait(trait_def) => process_trait_def(trait_def, &config),
+        Item::Impl(impl_block) => process_impl_block(impl_block, &config),
+        Item::Fn(function) => process_bare_function(function, &config),
+        _ => {
+            panic!("This macro can only be used on functions, traits, implementations or modules.")
+        }
     };
 
-    let mut enums = Vec::new();
-    for (i, error_set) in &functions {
-        let enum_ident = name_composed_error(i, &input_scope);
-
-        let derive_attr = quote!(#[derive(compounderr::thiserror::Error, Debug)]);
-        let from_attr = quote!(#[from]);
-        let transparent_attr = quote!(#[error(transparent)]);
-
-        enums.push(quote! {
-            #derive_attr
-            pub enum #enum_ident {
-                #(
-                    #transparent_attr
-                    #error_set(#from_attr #error_set)
-                ),*
-            }
+    // Nested functions and closures in bodies get their own, block scoped enums
+    #[cfg(feature = "body-analysis")]
+    body::compose_nested(&mut ast, &config);
 
-            #(
-                impl TryFrom<#enum_ident> for #error_set {
-                    type Error = String;
-                    fn try_from(value: #enum_ident) -> Result<Self, Self::Error> {
-                        match value {
-                            #enum_ident::#error_set(e) => Ok(e),
-                            _ => Err(
-                                    concat!(
-                                        "This instance of ", stringify!(#enum_ident),
-                                        " is of a variant different than the requested ", stringify!(#error_set)
-                                    ).to_string()
-                                ),
-                        }
-                    }
-                }
-            )*
+    let typed_siblings = add_typed_siblings(&mut ast, &mut functions, |func| {
+        config.signature_type(&config.name_enum(func, &input_scope), func.within.as_ref())
+    });
 
-        });
-    }
+    let diagnostics = functions
+        .iter()
+        .filter_map(|func| func.diagnostics.as_ref())
+        .map(syn::Error::to_compile_error);
+    let usage_warnings = functions
+        .iter()
+        .filter(|_| config.check_usage)
+        .map(usage_warnings);
+    let composed = compose_scope_errors(&input_scope, &functions, &config);
+
+    // Traits leave their errorsets behind, for subtraits to inherit
+    let inheritable = match &ast {
+        Item::Trait(trait_def) => inheritable_errorsets(&trait_def.ident, &functions, |func| {
+            config.signature_type(&config.name_enum(func, &input_scope), func.within.as_ref())
+        }),
+        _ => TokenStream2::new(),
+    };
+    let mock = match &ast {
+        Item::Trait(trait_def) if config.mock => compose_trait_mock(trait_def),
+        _ if config.mock => syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "mock can only be generated for traits",
+        )
+        .to_compile_error(),
+        _ => TokenStream2::new(),
+    };
 
     // Return the generated code
     TokenStream::from(quote! {
-        #(#enums)*
+        #relative
+        #unannotated
+
+        #(#diagnostics)*
+
+        #(#usage_warnings)*
+
+        #composed
+
+        #inheritable
 
         #ast
+
+        #typed_siblings
+
+        #mock
     })
 }
 
-type FuncErrors = (Ident, Vec<Ident>);
-type ScopeFuncs = (String, Vec<FuncErrors>);
+// The enums of a scope's functions, with the conversions between them and the catalog
+// and index over them
+fn compose_scope_errors(
+    input_scope: &str,
+    functions: &[FuncErrors],
+    config: &ComposeConfig,
+) -> TokenStream2 {
+    let enums = functions.iter().map(|func| {
+        let enum_ident = config.name_enum(func, input_scope);
+        let config = config.for_function(func);
+        let composed = if func.newtype {
+            compose_newtype_error(&enum_ident, &func.error_set, &config)
+        } else {
+            compose_error_enum(&enum_ident, &func.error_set, &config)
+        };
+        gate_items(composed, &func.cfgs)
+    });
+    let sibling_conversions = compose_sibling_conversions(
+        functions,
+        |func| config.name_enum(func, input_scope),
+        config,
+    );
+    let catalog = if config.catalog {
+        compose_error_catalog(input_scope, functions, config)
+    } else {
+        TokenStream2::new()
+    };
+    let index = if config.index {
+        compose_scope_index(input_scope, functions, config)
+    } else {
+        TokenStream2::new()
+    };
+    let composed = quote! {
+        #(#enums)*
+
+        #sibling_conversions
+
+        #catalog
 
-fn process_trait_def(trait_def: &mut ItemTrait) -> ScopeFuncs {
-    // For a trait, use the trait name as the enum name
-    let scope_name = trait_def.ident.to_string() + "Trait";
-    let functions = extract_trait_functions(trait_def);
-    strip_trait_functions_attrs(trait_def, &scope_name);
-    (scope_name, functions)
+        #index
+    };
+    // Everything composed can be kept together in a module, signatures point into it
+    match &config.module {
+        Some(module) => {
+            let vis = &config.vis;
+            quote! {
+                #vis mod #module {
+                    use super::*;
+
+                    #composed
+                }
+            }
+        }
+        None => composed,
+    }
 }
 
-fn process_impl_block(impl_block: &mut ItemImpl) -> ScopeFuncs {
-    // For an implementation, use the type name as the enum name
-    let ident = match &*impl_block.self_ty {
-        syn::Type::Path(tp) => tp.path.segments.last().unwrap().ident.clone(),
-        _ => panic!("not supported tokens"),
+// Not public API. Invoked through the macros generated by `inheritable_errorsets` and
+// `export_errorset!`, with input `[Members..] super::method (compose_errors args) trait Child
+// { .. }`, or `module::*` in place of `super::method`. Splices the members in place of the
+// reference, qualified with the module for a glob, then composes again.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __inherit_errorset(input: TokenStream) -> TokenStream {
+    let InheritedErrorset {
+        members,
+        parent,
+        reference,
+        compose_args,
+        mut item,
+    } = parse_macro_input!(input as InheritedErrorset);
+
+    // `crate::base::*` brings in `IoError` as `IoError = crate::base::IoError`
+    let members = match glob_module(&reference) {
+        Some(module) => {
+            let members = split_on_commas(members)
+                .into_iter()
+                .map(|member| quote!(#member = #module::#member));
+            quote!(#(#members),*)
+        }
+        None => members,
+    };
+    // The supertrait's enum comes along, for the subtrait's to convert from. Newtypes have
+    // none, and paths from the crate root need no prefix.
+    let members = match parent {
+        Some((conversion, prefix)) if !conversion.is_empty() => {
+            let mut conversion: ParentConversion = match syn::parse2(conversion) {
+                Ok(conversion) => conversion,
+                Err(error) => return error.to_compile_error().into(),
+            };
+            let path = &conversion.enum_path;
+            let absolute = path.leading_colon.is_some() || path.segments[0].ident == "crate";
+            if !prefix.is_empty() && !absolute {
+                conversion.enum_path = parse_quote!(#prefix::#path);
+            }
+            let comma = (!members.is_empty()).then(|| quote!(,));
+            quote!(#members #comma __parent(#conversion))
+        }
+        _ => members,
     };
-    let scope_name = ident.to_string() + "Impl";
-    // If it's an impl trait, then abort.
-    if impl_block.trait_.is_some() {
-        panic!("Use this macro on the trait definition, not the implementation.")
+    let reference = reference.to_string();
+    let splice = |tokens: TokenStream2| -> TokenStream2 {
+        let entries = split_on_commas(tokens).into_iter().map(|entry| {
+            if entry.to_string() == reference {
+                members.clone()
+            } else {
+                entry
+            }
+        });
+        quote!(#(#entries),*)
     };
+    for attrs in item_fn_attrs(&mut item) {
+        for attr in attrs {
+            if !is_errorset_attr(attr) {
+                continue;
+            }
+            if let syn::Meta::List(list) = &mut attr.meta {
+                list.tokens = splice(list.tokens.clone());
+            }
+        }
+    }
 
-    let functions = extract_impl_functions(impl_block);
-    strip_impl_functions_attrs(impl_block, &scope_name);
-    (scope_name, functions)
+    TokenStream::from(quote! {
+        #[::composerr::compose_errors(#compose_args)]
+        #item
+    })
 }
 
-fn process_bare_function(function: &mut ItemFn) -> ScopeFuncs {
-    // For bare function, use it's own name as the scope name
-    let scope_name = "".to_owned();
-    let functions = extract_bare_function(function);
-    strip_bare_function_attrs(function, "");
-    (scope_name, functions)
+/// Exports the error types of a module, for errorsets to import them all.
+///
+/// Proc macros only see the item they are applied to, so a module lists its errors once,
+/// and `#[errorset(path::to::module::*)]` makes each a member, named after its type.
+///
+/// ```ignore
+/// // In src/my_base_errors.rs
+/// composerr::export_errorset!(BasedError, IoError, ConfigError);
+///
+/// #[compose_errors]
+/// #[errorset(crate::my_base_errors::*, ParseIntError)]
+/// fn load() -> Result<Config, _> { /* ... */ }
+/// ```
+#[proc_macro]
+pub fn export_errorset(input: TokenStream) -> TokenStream {
+    let parser = syn::punctuated::Punctuated::<Ident, syn::Token![,]>::parse_terminated;
+    let members = parse_macro_input!(input with parser);
+    let members = members.iter();
+    let macro_ident = name_export_macro();
+    TokenStream::from(quote! {
+        #[doc(hidden)]
+        #[allow(unused_macros)]
+        macro_rules! #macro_ident {
+            ($($child:tt)*) => {
+                ::composerr::__inherit_errorset! { [#(#members),*] $($child)* }
+            };
+        }
+        #[allow(unused_imports)]
+        pub(crate) use #macro_ident;
+    })
 }
 
-fn extract_trait_functions(trait_def: &ItemTrait) -> Vec<FuncErrors> {
-    trait_def
-        .items
-        .iter()
-        // We want only function items
-        .filter_map(|item| match item {
-            TraitItem::Fn(item) => Some(item),
-            _ => None,
-        })
-        // and only those functions with #[errorset] attribute
-        .filter(|item| {
-            item.attrs
-                .iter()
-                .any(|attr| attr.path().get_ident().unwrap() == "errorset")
-        })
-        .map(|item| {
-            let func_name = item.sig.ident.clone();
-            let errorset_attr = item
-                .attrs
-                .iter()
-                .find(|attr| attr.path().is_ident("errorset"))
-                .unwrap();
-            let err_set: Vec<Ident> = extract_errorset_list(errorset_attr);
-            (func_name, err_set)
-        })
-        .collect()
+/// Registers a scope shared by several `#[compose_errors(scope = ...)]` items.
+///
+/// Items across files then name their enums after the scope, `MyServiceLoadError`, and the
+/// enums implement the `MyServiceErrors` marker trait declared here.
+///
+/// ```ignore
+/// register_error_scope!(pub MyService);
+///
+/// #[compose_errors(scope = crate::MyService)]
+/// impl Accounts { /* ... */ }
+/// ```
+#[proc_macro]
+pub fn register_error_scope(input: TokenStream) -> TokenStream {
+    let RegisteredScope { vis, scope } = parse_macro_input!(input as RegisteredScope);
+    let marker = name_scope_marker(&scope.clone().into());
+    TokenStream::from(quote! {
+        /// Implemented by the composed errors of the
+        #[doc = concat!("`", stringify!(#scope), "`")]
+        /// scope, as registered with `register_error_scope!`.
+        #vis trait #marker: ::core::error::Error {}
+    })
 }
 
-fn extract_impl_functions(impl_block: &ItemImpl) -> Vec<FuncErrors> {
-    impl_block
-        .items
-        .iter()
-        // We want only function items
-        .filter_map(|item| match item {
-            ImplItem::Fn(item) => Some(item),
-            _ => None,
-        })
-        // and only those functions with #[errorset] attribute
-        .filter(|item| {
-            item.attrs
-                .iter()
-                .any(|attr| attr.path().segments.last().unwrap().ident == "errorset")
-        })
-        .map(|item| {
-            let func_name = item.sig.ident.clone();
-            let errorset_attr = item
-                .attrs
-                .iter()
-                .find(|attr| attr.path().is_ident("errorset"))
-                .unwrap();
-            let err_set: Vec<Ident> = extract_errorset_list(errorset_attr);
-            (func_name, err_set)
-        })
-        .collect()
+// Input of `register_error_scope!`, `pub MyService`
+struct RegisteredScope {
+    vis: syn::Visibility,
+    scope: Ident,
 }
 
-fn extract_bare_function(function: &ItemFn) -> Vec<FuncErrors> {
-    if function
-        .attrs
-        .iter()
-        .any(|attr| attr.path().segments.last().unwrap().ident == "errorset")
-    {
-        let func_name = function.sig.ident.clone();
-        let errorset_attr = function
-            .attrs
-            .iter()
-            .find(|attr| attr.path().is_ident("errorset"))
-            .unwrap();
-        let err_set: Vec<Ident> = extract_errorset_list(errorset_attr);
-
-        vec![(func_name, err_set)]
-    } else {
-        vec![]
+impl Parse for RegisteredScope {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(RegisteredScope {
+            vis: input.parse()?,
+            scope: input.parse()?,
+        })
     }
 }
 
-fn extract_errorset_list(attr: &Attribute) -> Vec<Ident> {
-    let mut idents = Vec::new();
-    attr.parse_nested_meta(|meta| {
-        let ident = meta
-            .path
-            .get_ident()
-            .expect("Each item must be an ident, not long path");
-        idents.push(ident.clone());
-        Ok(())
+/// Declares the `ErrorMeta` and `VariantMeta` types of error catalogs.
+///
+/// Catalogs of several scopes share them with `#[compose_errors(catalog = "path")]`,
+/// the path of the module invoking this macro, so they can be listed together.
+///
+/// ```ignore
+/// // In src/errors.rs
+/// composerr::error_catalog_types!();
+///
+/// #[compose_errors(catalog = "crate::errors")]
+/// impl Foo { /* ... */ }
+/// ```
+#[proc_macro]
+pub fn error_catalog_types(_input: TokenStream) -> TokenStream {
+    TokenStream::from(catalog_types())
+}
+
+/// Declares the `ProblemDetails` type returned by `to_problem()`, in the module invoking
+/// it, `crate` unless another is given with `#[compose_errors(problem = "path")]`.
+///
+/// Attributes given are applied to the type, as derives serializing it. Its `r#type`
+/// field keeps the RFC 7807 member name, so serde renders it as `"type"`.
+///
+/// ```ignore
+/// // In src/lib.rs
+/// composerr::problem_details_type!(#[derive(serde::Serialize)]);
+///
+/// #[compose_errors(problem)]
+/// impl Foo { /* ... */ }
+/// ```
+#[proc_macro]
+pub fn problem_details_type(input: TokenStream) -> TokenStream {
+    let attrs = parse_macro_input!(input with Attribute::parse_outer);
+    TokenStream::from(quote! {
+        /// An error as RFC 7807 problem details.
+        #(#attrs)*
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct ProblemDetails {
+            /// URI identifying the problem type, `about:blank` unless annotated.
+            pub r#type: &'static str,
+            /// Short summary of the problem type.
+            pub title: &'static str,
+            /// HTTP status code, 500 unless annotated.
+            pub status: u16,
+            /// Explanation of this occurrence, the error's Display.
+            pub detail: String,
+            /// URI identifying this occurrence.
+            pub instance: Option<String>,
+        }
     })
-    .expect("Failed parsing args for errorset helper attribute");
-    idents
 }
 
-// Mutates ItemTrait in place. Removing the #[errorset] helper attribute
-// Also changes the return Result type, installing the custom composed error.
-fn strip_trait_functions_attrs(trait_def: &mut ItemTrait, scope_name: &str) {
-    let cleaned_items = trait_def
-        .items
-        .iter()
-        .map(|item| match item {
-            TraitItem::Fn(item_fn) => {
-                let mut item_fn = item_fn.clone();
-                if item_fn
-                    .attrs
-                    .iter()
-                    .any(|attr| attr.path().get_ident().unwrap() == "errorset")
-                {
-                    replace_func_output(
-                        &mut item_fn.sig.output,
-                        &name_composed_error(&item_fn.sig.ident, scope_name),
-                    );
-                }
-                item_fn
-                    .attrs
-                    .retain(|attr| attr.path().segments.last().unwrap().ident != "errorset");
-                TraitItem::Fn(item_fn)
+/// Declares the `Severity` type returned by `severity()`, in the module invoking it, `crate`
+/// unless another is given with `#[compose_errors(severity = "path")]`. The enums of every
+/// scope share it.
+///
+/// Attributes given are applied to the type, as more derives.
+///
+/// ```ignore
+/// // In src/lib.rs
+/// composerr::severity_type!();
+///
+/// #[compose_errors]
+/// #[errorset(IoError(retryable), ConfigError(fatal))]
+/// fn run_job() -> Result<(), _> { /* ... */ }
+/// ```
+#[proc_macro]
+pub fn severity_type(input: TokenStream) -> TokenStream {
+    let attrs = parse_macro_input!(input with Attribute::parse_outer);
+    TokenStream::from(quote! {
+        /// How severe an error is, as annotated on its errorset member. Ordered from least to
+        /// most severe.
+        #(#attrs)*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub enum Severity {
+            /// Annotated `warning`.
+            Warning,
+            /// The default.
+            Error,
+            /// Annotated `fatal`.
+            Fatal,
+        }
+    })
+}
+
+/// Declares the `ComposedError` trait, in the module invoking it, `crate` unless another is
+/// given with `#[compose_errors(composed_trait = "path")]`. Enums composed with the
+/// `composed_trait` option implement it with a single method, and take `get()`, `chain()` and
+/// `as_dyn_error()` from its default methods rather than generating them each.
+///
+/// With `boxed`, the trait also has `into_boxed()`, which needs std.
+///
+/// ```ignore
+/// // In src/lib.rs
+/// composerr::composed_error_trait!();
+///
+/// #[compose_errors(composed_trait)]
+/// #[errorset(IoError, ParseIntError)]
+/// fn load() -> Result<u8, _> { /* ... */ }
+///
+/// use crate::ComposedError;
+/// let io_error = load().unwrap_err().get::<IoError>();
+/// ```
+#[proc_macro]
+pub fn composed_error_trait(input: TokenStream) -> TokenStream {
+    let boxed = parse_macro_input!(input as Option<Ident>);
+    let into_boxed = match boxed {
+        Some(boxed) if boxed == "boxed" => quote! {
+            /// The error boxed as a trait object, for APIs taking `Box<dyn Error + Send + Sync>`.
+            fn into_boxed(self) -> ::std::boxed::Box<dyn ::core::error::Error + Send + Sync>
+            where
+                Self: Sized + Send + Sync,
+            {
+                ::std::boxed::Box::new(self)
             }
-            _ => item.clone(),
-        })
-        .collect();
-    trait_def.items = cleaned_items;
+        },
+        Some(other) => {
+            return syn::Error::new(other.span(), "the only argument taken is `boxed`")
+                .to_compile_error()
+                .into()
+        }
+        None => TokenStream2::new(),
+    };
+    TokenStream::from(quote! {
+        /// Access to the error held by an enum composed with the `composed_trait` option.
+        pub trait ComposedError: ::core::error::Error + 'static {
+            /// The inner error, `None` for variants declared inline.
+            fn member(&self) -> Option<&dyn ::core::any::Any>;
+
+            /// Returns a reference to the inner error if it is of type `E`,
+            /// like `downcast_ref` on `dyn Error`.
+            fn get<E: 'static>(&self) -> Option<&E> {
+                self.member()?.downcast_ref::<E>()
+            }
+
+            /// Iterates over the error and its sources, outermost first, like anyhow's `chain()`.
+            fn chain(&self) -> impl Iterator<Item = &(dyn ::core::error::Error + 'static)>
+            where
+                Self: Sized,
+            {
+                let mut next: Option<&(dyn ::core::error::Error + 'static)> = Some(self);
+                ::core::iter::from_fn(move || {
+                    let current = next?;
+                    next = current.source();
+                    Some(current)
+                })
+            }
+
+            /// The error as a trait object, for APIs taking `&dyn Error`.
+            fn as_dyn_error(&self) -> &(dyn ::core::error::Error + 'static)
+            where
+                Self: Sized,
+            {
+                self
+            }
+
+            #into_boxed
+        }
+    })
+}
+
+/// Helper for closures inside a `#[compose_errors]` function.
+///
+/// Used as a `let` initializer, it composes an error enum named after the binding
+/// and installs it in the closure's `Result<T, _>` return type.
+///
+/// ```ignore
+/// let parse_num = errorset_closure!(ParseIntError, IoError => |s: &str| -> Result<u32, _> {
+///     Ok(s.parse()?)
+/// });
+/// // parse_num returns Result<u32, ParseNumError>
+/// ```
+#[proc_macro]
+pub fn errorset_closure(_input: TokenStream) -> TokenStream {
+    // Expansion happens inside #[compose_errors]; reaching here means it was used elsewhere.
+    TokenStream::from(quote! {
+        compile_error!("`errorset_closure!` must be a `let` initializer inside a #[compose_errors] function")
+    })
 }
 
-// Mutates ItemImpl in place. Removing the #[errorset] helper attribute
-// Also changes the return Result type, installing the custom composed error.
-fn strip_impl_functions_attrs(impl_block: &mut ItemImpl, scope_name: &str) {
-    let cleaned_items = impl_block
-        .items
+/// Declares error sets with the syntax of the `error_set` crate, to ease migrating between the two.
+///
+/// Each `Name = { .. } || OtherSet;` declaration becomes a composed enum called `Name`.
+/// Entries are either `Variant(Type)`, wrapping an error type under the given variant name,
+/// or a bare `Type` used as both (composerr convention, unlike fieldless variants in `error_set`).
+/// Sets joined with `||` are unioned, and a set converts into every set containing it.
+///
+/// ```ignore
+/// error_set! {
+///     MediaError = {
+///         IoError(std::io::Error),
+///     } || BookParsingError;
+///     BookParsingError = { ParseIntError, Utf8Error };
+/// }
+/// ```
+#[proc_macro]
+pub fn error_set(input: TokenStream) -> TokenStream {
+    let ErrorSetDecls(decls) = parse_macro_input!(input as ErrorSetDecls);
+    let config = ComposeConfig::default();
+
+    let mut sets = Vec::new();
+    for decl in &decls {
+        match resolve_error_set(&decls, &decl.name, &mut Vec::new()) {
+            Ok(error_set) => sets.push(FuncErrors {
+                name: decl.name.clone(),
+                error_set,
+                cfgs: Vec::new(),
+                no_try_from: false,
+                copy: false,
+                sorted: false,
+                enum_name: None,
+                vis: None,
+                derives: Vec::new(),
+                vias: Vec::new(),
+                parents: Vec::new(),
+                context: false,
+                origin: None,
+                within: None,
+                body: None,
+                typed: false,
+                newtype: false,
+                diagnostics: None,
+            }),
+            Err(err) => return err.to_compile_error().into(),
+        }
+    }
+
+    let enums = sets
         .iter()
-        .map(|item| match item {
-            ImplItem::Fn(item_fn) => {
-                let mut item_fn = item_fn.clone();
-                if item_fn
-                    .attrs
-                    .iter()
-                    .any(|attr| attr.path().segments.last().unwrap().ident == "errorset")
-                {
-                    replace_func_output(
-                        &mut item_fn.sig.output,
-                        &name_composed_error(&item_fn.sig.ident, scope_name),
-                    );
+        .map(|set| compose_error_enum(&set.name, &set.error_set, &config));
+    let conversions = compose_sibling_conversions(&sets, |set| set.name.clone(), &config);
+    TokenStream::from(quote! {
+        #(#enums)*
+
+        #conversions
+    })
+}
+
+// Declarations of the `error_set!` compatibility macro
+struct ErrorSetDecls(Vec<ErrorSetDecl>);
+
+// `Name = { Variant(Type), Type } || OtherSet;`
+struct ErrorSetDecl {
+    name: Ident,
+    parts: Vec<ErrorSetPart>,
+}
+
+enum ErrorSetPart {
+    Members(Vec<ErrorMember>),
+    Union(Ident),
+}
+
+impl Parse for ErrorSetDecls {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut decls = Vec::new();
+        while !input.is_empty() {
+            let name: Ident = input.parse()?;
+            input.parse::<syn::Token![=]>()?;
+            let mut parts = Vec::new();
+            loop {
+                if input.peek(syn::token::Brace) {
+                    let entries;
+                    syn::braced!(entries in input);
+                    let entries =
+                        entries.parse_terminated(parse_error_set_entry, syn::Token![,])?;
+                    parts.push(ErrorSetPart::Members(entries.into_iter().collect()));
+                } else {
+                    parts.push(ErrorSetPart::Union(input.parse()?));
+                }
+                if !input.peek(syn::Token![||]) {
+                    break;
                 }
-                item_fn
-                    .attrs
-                    .retain(|attr| attr.path().segments.last().unwrap().ident != "errorset");
-                ImplItem::Fn(item_fn)
+                input.parse::<syn::Token![||]>()?;
             }
-            _ => item.clone(),
-        })
-        .collect();
-    impl_block.items = cleaned_items;
+            input.parse::<syn::Token![;]>()?;
+            decls.push(ErrorSetDecl { name, parts });
+        }
+        Ok(ErrorSetDecls(decls))
+    }
 }
 
-// Mutates function in place. Removing the #[errorset] helper attribute
-// Also changes the return Result type, installing the custom composed error.
-fn strip_bare_function_attrs(function: &mut ItemFn, scope_name: &str) {
-    replace_func_output(
-        &mut function.sig.output,
-        &name_composed_error(&function.sig.ident, scope_name),
-    );
-    function
-        .attrs
-        .retain(|attr| attr.path().segments.last().unwrap().ident != "errorset");
+// `Variant(Type)` or a bare `Type`
+fn parse_error_set_entry(input: ParseStream) -> syn::Result<ErrorMember> {
+    let variant: Ident = input.parse()?;
+    if input.peek(syn::token::Paren) {
+        let ty;
+        syn::parenthesized!(ty in input);
+        Ok(ErrorMember::new(variant, ty.parse()?))
+    } else if input.peek(syn::token::Brace) {
+        Err(input.error("struct variants are not supported, wrap an error type instead"))
+    } else {
+        Ok(ErrorMember::from(variant))
+    }
 }
 
-// This function takes a mutable reference to function ReturnType and attempts to modify it.
-// Replaces the error variant if it is a Result type.
-fn replace_func_output(return_type: &mut ReturnType, composed_error_ident: &Ident) {
-    // Replace the error type in the return type with CustomE
-    // This is a simplification; you'll need to adjust this based on how your types are structured
-    if let ReturnType::Type(_, return_type) = return_type {
-        if let Type::Path(type_path) = &mut **return_type {
-            let path = &mut type_path.path;
-            if path.segments.first().unwrap().ident == "Result" {
-                if let PathArguments::AngleBracketed(result_args) =
-                    &mut path.segments.first_mut().unwrap().arguments
-                {
-                    let args = &mut result_args.args;
-                    // Result <T, E>
-                    if args.len() == 2 {
-                        let composed_error_type: Type = parse_quote!(#composed_error_ident);
-                        // Check if the second argument is a generic argument of type inference
-                        if let GenericArgument::Type(Type::Infer(_)) = &args[1] {
-                            // Replace the inferred type with the composed error type
-                            args[1] = GenericArgument::Type(composed_error_type.clone());
-                        }
-                    }
+// All members of a declared set, following `||` unions
+fn resolve_error_set(
+    decls: &[ErrorSetDecl],
+    name: &Ident,
+    visiting: &mut Vec<Ident>,
+) -> syn::Result<Vec<ErrorMember>> {
+    if visiting.contains(name) {
+        return Err(syn::Error::new(name.span(), "error sets form a cycle"));
+    }
+    let decl = decls
+        .iter()
+        .find(|decl| &decl.name == name)
+        .ok_or_else(|| syn::Error::new(name.span(), "error set not declared in this error_set!"))?;
+    visiting.push(name.clone());
+    let mut error_set: Vec<ErrorMember> = Vec::new();
+    for part in &decl.parts {
+        let members = match part {
+            ErrorSetPart::Members(members) => members.clone(),
+            ErrorSetPart::Union(other) => resolve_error_set(decls, other, visiting)?,
+        };
+        for member in members {
+            match error_set.iter().find(|m| m.variant == member.variant) {
+                Some(existing) if existing.same_type(&member) => {}
+                Some(_) => {
+                    return Err(syn::Error::new(
+                        member.variant.span(),
+                        "variant already in the set, wrapping a different type",
+                    ))
                 }
+                None => error_set.push(member),
             }
         }
     }
+    visiting.pop();
+    Ok(error_set)
 }
 
-//
-fn name_composed_error(function_ident: &Ident, prefix: &str) -> Ident {
-    let name = format!(
-        "{}{}{}",
-        snake_to_pascal(prefix),
-        snake_to_pascal(&function_ident.to_string()),
-        "Error"
-    );
-    Ident::new(&name, function_ident.span())
+// A function carrying an errorset, as extracted from its scope
+struct FuncErrors {
+    name: Ident,
+    error_set: Vec<ErrorMember>,
+    // The function's own #[cfg] attributes, everything generated for it is gated the same
+    cfgs: Vec<Attribute>,
+    // Overrides from the errorset, see `ComposeConfig::for_function`
+    no_try_from: bool,
+    copy: bool,
+    sorted: bool,
+    enum_name: Option<Ident>,
+    vis: Option<syn::Visibility>,
+    derives: Vec<syn::Path>,
+    vias: Vec<ViaConversion>,
+    parents: Vec<ParentConversion>,
+    context: bool,
+    // Path of the function, for doc links, `Foo::load` for a method
+    origin: Option<syn::Path>,
+    // The module re-exporting the enum, `in crate::errors`
+    within: Option<syn::Path>,
+    // The body as written, before any rewriting, for `check_usage`
+    body: Option<TokenStream2>,
+    // Gets a `foo_typed` sibling, see `add_typed_siblings`
+    typed: bool,
+    // Composed as a struct around its one member, see `compose_newtype_error`
+    newtype: bool,
+    // Malformed arguments and members listed more than once, reported next to the
+    // generated enum
+    diagnostics: Option<syn::Error>,
 }
 
-fn snake_to_pascal(snake_case: &str) -> String {
-    // Split the snake_case string into words
-    let words: Vec<&str> = snake_case.split('_').collect();
-
-    // Convert each word to PascalCase
-    let pascal_words: Vec<String> = words
-        .into_iter()
-        .map(|word| {
-            if word.is_empty() {
-                return String::new();
+impl FuncErrors {
+    fn new(sig: &Signature, attrs: &[Attribute], body: Option<&Block>, args: ErrorsetArgs) -> Self {
+        let (name, output) = (&sig.ident, &sig.output);
+        let (mut error_set, invalid) = args.error_set(output);
+        let mut diagnostics = args.error.clone();
+        let unused = (args.extend.is_none()
+            && !args.throws
+            && args.typed.is_none()
+            && !has_inferred_error(sig))
+        .then(|| {
+            let message = "errorset needs a `Result<T, _>` in the return type to put the enum in, \
+                or `extend` to keep the error already there";
+            match output {
+                ReturnType::Type(_, return_type) => syn::Error::new_spanned(return_type, message),
+                ReturnType::Default => syn::Error::new(name.span(), message),
+            }
+        });
+        // An opaque return type can't be wrapped in a Result, nor its body in `Ok`
+        let opaque_throws = (args.throws && is_impl_trait_output(output)).then(|| {
+            syn::Error::new_spanned(
+                output,
+                "errorset(throws) can't wrap an `impl Trait` return type, \
+                write `async fn` or the Result in its bounds, `impl Future<Output = Result<T, _>>`",
+            )
+        });
+        // Substituted away when installing the enum, which `extend` and `typed` skip
+        let generic_conflict = (args.extend.is_none() && args.typed.is_none())
+            .then(|| generic_error_conflict(sig))
+            .flatten();
+        for error in invalid
+            .into_iter()
+            .chain(generic_conflict)
+            .chain(unused)
+            .chain(opaque_throws)
+            .chain(dedupe_members(&mut error_set))
+        {
+            match &mut diagnostics {
+                Some(diagnostics) => diagnostics.combine(error),
+                None => diagnostics = Some(error),
             }
-            let mut chars = word.chars();
-            match chars.next() {
-                Some(first) => format!("{}", first.to_ascii_uppercase()) + chars.as_str(),
-                None => String::new(),
+        }
+        FuncErrors {
+            name: name.clone(),
+            no_try_from: args.no_try_from,
+            copy: args.copy,
+            sorted: args.sorted,
+            error_set,
+            diagnostics,
+            enum_name: args.name,
+            vis: args.vis,
+            derives: args.derives,
+            vias: args.vias,
+            parents: args.parents,
+            context: args.context,
+            origin: Some(name.clone().into()),
+            within: args.within,
+            body: body.map(ToTokens::to_token_stream),
+            typed: args.typed.is_some(),
+            newtype: args.newtype,
+            cfgs: attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("cfg"))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+// Members the body never produces, warned about through a deprecated item, as stable
+// proc macros can't emit warnings. Judged from the tokens alone: a member counts as
+// produced when the body names its variant, constructor or type. Wrapped members are
+// only reported when nothing converts implicitly, with no `?`, `into` or `from`.
+fn usage_warnings(func: &FuncErrors) -> TokenStream2 {
+    fn scan(tokens: TokenStream2, idents: &mut Vec<String>, converts: &mut bool) {
+        for tree in tokens {
+            match tree {
+                proc_macro2::TokenTree::Group(group) => scan(group.stream(), idents, converts),
+                proc_macro2::TokenTree::Ident(ident) => {
+                    let ident = ident.to_string();
+                    *converts |= ["into", "from", "try_into", "map_err", "ctx", "with_ctx"]
+                        .contains(&ident.as_str());
+                    idents.push(ident);
+                }
+                proc_macro2::TokenTree::Punct(punct) => *converts |= punct.as_char() == '?',
+                proc_macro2::TokenTree::Literal(_) => {}
             }
+        }
+    }
+    let Some(body) = &func.body else {
+        return TokenStream2::new();
+    };
+    let (mut idents, mut converts) = (Vec::new(), false);
+    scan(body.clone(), &mut idents, &mut converts);
+    let warnings = func.error_set.iter().filter_map(|member| {
+        let variant = member.variant.to_string();
+        let mut names = vec![pascal_to_snake(&variant), variant];
+        if let Type::Path(ty) = &member.ty {
+            names.extend(ty.path.segments.last().map(|last| last.ident.to_string()));
+        }
+        let named = names.iter().any(|name| idents.contains(name));
+        if named || (converts && member.inline.is_none()) {
+            return None;
+        }
+        let note = format!(
+            "`{}` is never produced by `{}`, consider dropping it from the errorset",
+            member.variant, func.name
+        );
+        let cfg = member.cfg_attr();
+        Some(quote_spanned! {member.variant.span()=>
+            #cfg
+            const _: () = {
+                #[deprecated(note = #note)]
+                #[allow(non_upper_case_globals)]
+                const unused_errorset_member: () = ();
+                unused_errorset_member
+            };
         })
-        .collect();
+    });
+    gate_items(quote!(#(#warnings)*), &func.cfgs)
+}
+
+// Removes members repeated in a set, keeping the first. Each repeat is reported
+// at its span, as duplicate variants would otherwise break the generated enum.
+// The same member under different cfgs is fine, those may be mutually exclusive.
+fn dedupe_members(error_set: &mut Vec<ErrorMember>) -> Option<syn::Error> {
+    let mut kept: Vec<ErrorMember> = Vec::with_capacity(error_set.len());
+    let mut duplicates: Option<syn::Error> = None;
+    for member in error_set.drain(..) {
+        let cfg = member.cfg.as_ref().map(ToString::to_string);
+        let repeated = kept.iter().any(|other| {
+            other.variant == member.variant && other.cfg.as_ref().map(ToString::to_string) == cfg
+        });
+        if !repeated {
+            kept.push(member);
+            continue;
+        }
+        let error = syn::Error::new(
+            member.variant.span(),
+            format!(
+                "`{}` is listed more than once in this errorset",
+                member.variant
+            ),
+        );
+        match &mut duplicates {
+            Some(duplicates) => duplicates.combine(error),
+            None => duplicates = Some(error),
+        }
+    }
+    *error_set = kept;
+    duplicates
+}
 
-    // Join the words back together without any separators
-    pascal_words.join("")
+// Adds the attributes (typically `#[cfg]`s) to each of the generated items
+fn gate_items(items: TokenStream2, gates: &[Attribute]) -> TokenStream2 {
+    if gates.is_empty() {
+        return items;
+    }
+    let mut file: syn::File = syn::parse2(items).expect("generated items should parse");
+    for item in &mut file.items {
+        let attrs = match item {
+            Item::Const(item) => &mut item.attrs,
+            Item::Enum(item) => &mut item.attrs,
+            Item::Fn(item) => &mut item.attrs,
+            Item::Impl(item) => &mut item.attrs,
+            Item::Macro(item) => &mut item.attrs,
+            Item::Mod(item) => &mut item.attrs,
+            Item::Static(item) => &mut item.attrs,
+            Item::Struct(item) => &mut item.attrs,
+            Item::Trait(item) => &mut item.attrs,
+            Item::Type(item) => &mut item.attrs,
+            Item::Use(item) => &mut item.attrs,
+            _ => unreachable!("no other kind of item is generated"),
+        };
+        attrs.splice(0..0, gates.iter().cloned());
+    }
+    quote!(#file)
+}
+
+// The attributes of each function of the item, methods of a trait or impl block
+fn item_fn_attrs(item: &mut Item) -> Vec<&mut Vec<Attribute>> {
+    match item {
+        Item::Trait(trait_def) => trait_def
+            .items
+            .iter_mut()
+            .filter_map(|item| match item {
+                TraitItem::Fn(item) => Some(&mut item.attrs),
+                _ => None,
+            })
+            .collect(),
+        Item::Impl(impl_block) => impl_block
+            .items
+            .iter_mut()
+            .filter_map(|item| match item {
+                ImplItem::Fn(item) => Some(&mut item.attrs),
+                _ => None,
+            })
+            .collect(),
+        Item::Fn(function) => vec![&mut function.attrs],
+        _ => Vec::new(),
+    }
 }
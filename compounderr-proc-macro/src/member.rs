@@ -0,0 +1,584 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse::{Parse, ParseStream},
+    Attribute, Ident, Pat, Type,
+};
+
+use crate::{parse_meta_value, pascal_to_snake, preset_members, ComposeConfig};
+
+// A member of an error set, becoming the `variant(ty)` of the composed enum
+#[derive(Clone)]
+pub(crate) struct ErrorMember {
+    pub(crate) variant: Ident,
+    pub(crate) ty: Type,
+    // Annotations, as in `#[errorset(IoError(retryable, warning))]`
+    pub(crate) retryable: bool,
+    pub(crate) severity: Severity,
+    // For command line reporting, as in `IoError(exit = 74, hint = "check the path")`
+    pub(crate) exit_code: Option<syn::LitInt>,
+    pub(crate) hint: Option<syn::LitStr>,
+    // Explicit discriminant with a repr, as in `IoError(discriminant = 3)`
+    pub(crate) discriminant: Option<syn::LitInt>,
+    // GraphQL error extensions, as in `NotFound(code = "NOT_FOUND", status = 404)`
+    pub(crate) graphql_code: Option<syn::LitStr>,
+    pub(crate) http_status: Option<syn::LitInt>,
+    // RFC 7807 problem details, as in `NotFound(problem_type = "https://..", title = "..")`
+    pub(crate) problem_type: Option<syn::LitStr>,
+    pub(crate) title: Option<syn::LitStr>,
+    // Applied to the error in its From impl, as in `HttpError(map = sanitize_http)`
+    pub(crate) mapper: Option<syn::Path>,
+    // The trait of a `dyn Diagnostic` member, held boxed in the `Dyn` variant
+    pub(crate) dyn_trait: Option<syn::Path>,
+    // Predicate of `cfg(..) Member`, gating the variant and its impls
+    pub(crate) cfg: Option<TokenStream2>,
+    // Declared in place rather than wrapping a type, the `ty` is then unused
+    pub(crate) inline: Option<InlineVariant>,
+    // One of the variants a type is routed to, by matching on the error
+    pub(crate) route: Option<Route>,
+    // Wraps the same type as a variant listed before it, `Write = IoError` after
+    // `Read = IoError`. Only the first converts with From, this one has a constructor.
+    pub(crate) shared: bool,
+    // Built by the enum's `Default` impl, as in `default Unknown : "unknown error"`
+    pub(crate) default: bool,
+}
+
+// A variant declared in the errorset, `NotFound { path: PathBuf } : "missing file {path}"`
+#[derive(Clone)]
+pub(crate) struct InlineVariant {
+    pub(crate) fields: Option<syn::FieldsNamed>,
+    pub(crate) message: syn::LitStr,
+}
+
+impl InlineVariant {
+    pub(crate) fn field_names(&self) -> Vec<&Ident> {
+        self.fields
+            .iter()
+            .flat_map(|fields| &fields.named)
+            .filter_map(|field| field.ident.as_ref())
+            .collect()
+    }
+}
+
+// The enum of an inherited supertrait method and its variants, which the subtrait's enum
+// has too, `#[cfg(..)] ParentTraitLoadError => IoError, ParseError`
+#[derive(Clone)]
+pub(crate) struct ParentConversion {
+    pub(crate) cfgs: Vec<Attribute>,
+    pub(crate) enum_path: syn::Path,
+    pub(crate) variants: Vec<Ident>,
+}
+
+impl Parse for ParentConversion {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let cfgs = input.call(Attribute::parse_outer)?;
+        let enum_path = input.parse()?;
+        input.parse::<syn::Token![=>]>()?;
+        let variants = input.parse_terminated(Ident::parse, syn::Token![,])?;
+        Ok(ParentConversion {
+            cfgs,
+            enum_path,
+            variants: variants.into_iter().collect(),
+        })
+    }
+}
+
+impl ToTokens for ParentConversion {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let ParentConversion {
+            cfgs,
+            enum_path,
+            variants,
+        } = self;
+        tokens.extend(quote!(#(#cfgs)* #enum_path => #(#variants),*));
+    }
+}
+
+// A type converting into the enum through one of its members, `via(RawDbError => DbError)`
+#[derive(Clone)]
+pub(crate) struct ViaConversion {
+    pub(crate) from: Type,
+    pub(crate) member: Type,
+    pub(crate) cfg: Option<TokenStream2>,
+}
+
+impl Parse for ViaConversion {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let from = input.parse()?;
+        input.parse::<syn::Token![=>]>()?;
+        let member = input.parse()?;
+        Ok(ViaConversion {
+            from,
+            member,
+            cfg: None,
+        })
+    }
+}
+
+// How a type routed to several variants picks this one, in its From impl
+#[derive(Clone)]
+pub(crate) enum Route {
+    // `io::NotFound`, an io::Error of that ErrorKind
+    IoKind(Ident),
+    // `IoOther`, an io::Error of the kinds not listed
+    IoOther,
+    // `map(sqlx::Error => { RowNotFound => NotFoundError, _ => DbError })`, the
+    // pattern of the arm, or None for `_`
+    Map(Option<Pat>),
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub(crate) enum Severity {
+    Warning,
+    #[default]
+    Error,
+    Fatal,
+}
+
+impl ErrorMember {
+    pub(crate) fn new(variant: Ident, ty: Type) -> Self {
+        ErrorMember {
+            variant,
+            ty,
+            retryable: false,
+            severity: Severity::default(),
+            exit_code: None,
+            hint: None,
+            discriminant: None,
+            graphql_code: None,
+            http_status: None,
+            problem_type: None,
+            title: None,
+            mapper: None,
+            dyn_trait: None,
+            cfg: None,
+            inline: None,
+            route: None,
+            shared: false,
+            default: false,
+        }
+    }
+
+    // Inline variants belong to their own enum, so never count as the same.
+    // Routed members only match members routed the same way.
+    pub(crate) fn same_type(&self, other: &ErrorMember) -> bool {
+        if self.inline.is_some() || other.inline.is_some() {
+            return false;
+        }
+        self.route_key() == other.route_key() && self.same_ty(other)
+    }
+
+    // Whether the two wrap the same type, however routed
+    pub(crate) fn same_ty(&self, other: &ErrorMember) -> bool {
+        let (ty, other_ty) = (&self.ty, &other.ty);
+        quote!(#ty).to_string() == quote!(#other_ty).to_string()
+    }
+
+    // The route as a comparable string
+    pub(crate) fn route_key(&self) -> Option<String> {
+        self.route.as_ref().map(|route| match route {
+            Route::IoKind(kind) => kind.to_string(),
+            Route::IoOther | Route::Map(None) => "_".to_string(),
+            Route::Map(Some(pattern)) => quote!(#pattern).to_string(),
+        })
+    }
+
+    pub(crate) fn cfg_attr(&self) -> TokenStream2 {
+        match &self.cfg {
+            Some(predicate) => quote!(#[cfg(#predicate)]),
+            None => TokenStream2::new(),
+        }
+    }
+
+    // Whether this member is converted into when its type matches a pattern,
+    // `io::NotFound` or a `map` arm other than `_`
+    pub(crate) fn is_routed(&self) -> bool {
+        matches!(self.route, Some(Route::IoKind(_) | Route::Map(Some(_))))
+    }
+
+    // Whether this member takes the rest of a routed type, `IoOther` or `_`
+    pub(crate) fn is_fallback(&self) -> bool {
+        matches!(self.route, Some(Route::IoOther | Route::Map(None)))
+    }
+
+    // Whether this is a `dyn Error` member, like the `Other` of typed functions
+    pub(crate) fn is_boxed_error(&self) -> bool {
+        self.dyn_trait
+            .as_ref()
+            .is_some_and(|dyn_trait| dyn_trait.segments.last().unwrap().ident == "Error")
+    }
+
+    // Whether this is the member of a preset, `preset::Utf8Error`
+    pub(crate) fn is_preset(&self) -> bool {
+        preset_members(&self.variant.to_string()).is_some_and(|presets| {
+            let ty = &self.ty;
+            presets
+                .iter()
+                .any(|(_, preset_ty)| quote!(#preset_ty).to_string() == quote!(#ty).to_string())
+        })
+    }
+
+    // `= N` after the variant, with an explicit discriminant
+    pub(crate) fn discriminant(&self) -> TokenStream2 {
+        match &self.discriminant {
+            Some(discriminant) => quote!(= #discriminant),
+            None => TokenStream2::new(),
+        }
+    }
+
+    // Whether this member exists whenever `other` does
+    pub(crate) fn available_with(&self, other: &ErrorMember) -> bool {
+        match (&self.cfg, &other.cfg) {
+            (None, _) => true,
+            (Some(predicate), Some(other_predicate)) => {
+                predicate.to_string() == other_predicate.to_string()
+            }
+            (Some(_), None) => false,
+        }
+    }
+
+    // The member written back as errorset syntax, annotations included
+    pub(crate) fn spec(&self) -> TokenStream2 {
+        let default = self.default.then(|| quote!(default));
+        let cfg = self
+            .cfg
+            .as_ref()
+            .map(|predicate| quote!(#default cfg(#predicate)));
+        let cfg = cfg.or(default);
+        let ty = &self.ty;
+        let mut annotations = Vec::new();
+        if self.retryable {
+            annotations.push(quote!(retryable));
+        }
+        match self.severity {
+            Severity::Warning => annotations.push(quote!(warning)),
+            Severity::Error => {}
+            Severity::Fatal => annotations.push(quote!(fatal)),
+        }
+        if let Some(exit_code) = &self.exit_code {
+            annotations.push(quote!(exit = #exit_code));
+        }
+        if let Some(hint) = &self.hint {
+            annotations.push(quote!(hint = #hint));
+        }
+        if let Some(discriminant) = &self.discriminant {
+            annotations.push(quote!(discriminant = #discriminant));
+        }
+        if let Some(code) = &self.graphql_code {
+            annotations.push(quote!(code = #code));
+        }
+        if let Some(status) = &self.http_status {
+            annotations.push(quote!(status = #status));
+        }
+        if let Some(problem_type) = &self.problem_type {
+            annotations.push(quote!(problem_type = #problem_type));
+        }
+        if let Some(title) = &self.title {
+            annotations.push(quote!(title = #title));
+        }
+        if let Some(mapper) = &self.mapper {
+            annotations.push(quote!(map = #mapper));
+        }
+        let annotations = if annotations.is_empty() {
+            TokenStream2::new()
+        } else {
+            quote!((#(#annotations),*))
+        };
+        let variant = &self.variant;
+        match (&self.inline, &self.route) {
+            (Some(InlineVariant { fields, message }), _) => {
+                quote!(#cfg #variant #fields #annotations : #message)
+            }
+            (None, Some(Route::IoKind(kind))) => quote!(#cfg io::#kind #annotations),
+            (None, Some(Route::IoOther)) => quote!(#cfg IoOther #annotations),
+            (None, Some(Route::Map(pattern))) => {
+                let pattern = match pattern {
+                    Some(pattern) => quote!(#pattern),
+                    None => quote!(_),
+                };
+                quote!(#cfg map(#ty => { #pattern => #variant #annotations }))
+            }
+            (None, None) if self.dyn_trait.is_some() => {
+                let dyn_trait = &self.dyn_trait;
+                quote!(#cfg dyn #dyn_trait #annotations)
+            }
+            (None, None) if self.is_preset() => quote!(#cfg preset::#variant #annotations),
+            (None, None) if *variant != quote!(#ty).to_string() => {
+                quote!(#cfg #variant = #ty #annotations)
+            }
+            (None, None) => quote!(#cfg #ty #annotations),
+        }
+    }
+
+    // Parses the annotations in `Member(annotation, ..)`
+    pub(crate) fn parse_annotation(&mut self, meta: syn::meta::ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("retryable") {
+            self.retryable = true;
+        } else if meta.path.is_ident("warning") {
+            self.severity = Severity::Warning;
+        } else if meta.path.is_ident("fatal") {
+            self.severity = Severity::Fatal;
+        } else if meta.path.is_ident("exit") {
+            let exit_code: syn::LitInt = meta.value()?.parse()?;
+            exit_code.base10_parse::<u8>()?;
+            self.exit_code = Some(exit_code);
+        } else if meta.path.is_ident("hint") {
+            self.hint = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("discriminant") {
+            self.discriminant = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("code") {
+            self.graphql_code = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("status") {
+            let status: syn::LitInt = meta.value()?.parse()?;
+            if !(100..=599).contains(&status.base10_parse::<u16>()?) {
+                return Err(syn::Error::new(
+                    status.span(),
+                    "HTTP status must be 100 to 599",
+                ));
+            }
+            self.http_status = Some(status);
+        } else if meta.path.is_ident("problem_type") {
+            self.problem_type = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("title") {
+            self.title = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("map") {
+            self.mapper = Some(parse_meta_value(&meta)?);
+        } else {
+            return Err(meta.error("unsupported errorset member annotation"));
+        }
+        Ok(())
+    }
+}
+
+// The From impls of types routed to several variants, one per type, matching on the
+// error (or its io::ErrorKind). `construct` builds the value of a variant from `source`.
+pub(crate) fn routed_from_impls(
+    enum_ident: &Ident,
+    error_set: &[ErrorMember],
+    fn_attrs: TokenStream2,
+    construct: impl Fn(&Ident) -> TokenStream2,
+) -> TokenStream2 {
+    let impls = error_set
+        .iter()
+        .filter(|member| member.is_fallback())
+        .map(|fallback| {
+            let ty = &fallback.ty;
+            let scrutinee = match fallback.route {
+                Some(Route::IoOther) => quote!(source.kind()),
+                _ => quote!(&source),
+            };
+            let arms = error_set
+                .iter()
+                .filter(|member| member.is_routed() && member.same_ty(fallback))
+                .map(|member| {
+                    let cfg = member.cfg_attr();
+                    let pattern = match &member.route {
+                        Some(Route::IoKind(kind)) => quote!(::std::io::ErrorKind::#kind),
+                        Some(Route::Map(Some(pattern))) => quote!(#pattern),
+                        _ => unreachable!("routed members have a pattern"),
+                    };
+                    let value = construct(&member.variant);
+                    quote!(#cfg #pattern => #value,)
+                });
+            let cfg = fallback.cfg_attr();
+            let rest = construct(&fallback.variant);
+            quote! {
+                #cfg
+                impl From<#ty> for #enum_ident {
+                    #fn_attrs
+                    fn from(source: #ty) -> Self {
+                        match #scrutinee {
+                            #(#arms)*
+                            _ => #rest,
+                        }
+                    }
+                }
+            }
+        });
+    quote!(#(#impls)*)
+}
+
+// Marks the members wrapping the same type as a member listed before them, see `shared`
+pub(crate) fn mark_shared_members(error_set: &mut [ErrorMember]) {
+    let convertible = |member: &ErrorMember| {
+        member.inline.is_none() && member.route.is_none() && member.dyn_trait.is_none()
+    };
+    for index in 0..error_set.len() {
+        let (before, rest) = error_set.split_at_mut(index);
+        let member = &mut rest[0];
+        member.shared = convertible(member)
+            && before
+                .iter()
+                .any(|other| convertible(other) && other.same_ty(member));
+    }
+}
+
+// `from_write(source)` constructors of the shared members, `construct` building the
+// value of a member's variant from `source`
+pub(crate) fn shared_constructors(
+    enum_ident: &Ident,
+    error_set: &[ErrorMember],
+    config: &ComposeConfig,
+    construct: impl Fn(&ErrorMember) -> TokenStream2,
+) -> TokenStream2 {
+    let track_caller = config.track_caller.then(|| quote!(#[track_caller]));
+    let shared: Vec<&ErrorMember> = error_set.iter().filter(|member| member.shared).collect();
+    if shared.is_empty() {
+        return TokenStream2::new();
+    }
+    let constructors = shared.into_iter().map(|member| {
+        let cfg = member.cfg_attr();
+        let ty = &member.ty;
+        let variant = &member.variant;
+        let constructor = format_ident!("from_{}", pascal_to_snake(&variant.to_string()));
+        let value = construct(member);
+        quote! {
+            #cfg
+            #[doc = concat!("Wraps the error in the `", stringify!(#variant), "` variant, `From` picks another.")]
+            #track_caller
+            pub fn #constructor(source: #ty) -> Self {
+                #value
+            }
+        }
+    });
+    quote! {
+        impl #enum_ident {
+            #(#constructors)*
+        }
+    }
+}
+
+// `Default` building the member marked `default`, from the defaults of its error or fields
+pub(crate) fn default_impl(
+    enum_ident: &Ident,
+    error_set: &[ErrorMember],
+    config: &ComposeConfig,
+    construct: impl Fn(&ErrorMember) -> TokenStream2,
+) -> TokenStream2 {
+    let defaults: Vec<&ErrorMember> = error_set.iter().filter(|member| member.default).collect();
+    let member = match defaults[..] {
+        [] => return TokenStream2::new(),
+        [member] => member,
+        [_, second, ..] => {
+            return syn::Error::new(second.variant.span(), "only one member can be the default")
+                .to_compile_error()
+        }
+    };
+    let cfg = member.cfg_attr();
+    let bindings = match &member.inline {
+        Some(inline) => {
+            let names = inline.field_names();
+            let types = inline
+                .fields
+                .iter()
+                .flat_map(|fields| &fields.named)
+                .map(|field| &field.ty);
+            quote!(#(let #names: #types = ::core::default::Default::default();)*)
+        }
+        None => {
+            let ty = &member.ty;
+            quote!(let source: #ty = ::core::default::Default::default();)
+        }
+    };
+    let track_caller =
+        (config.track_caller && member.inline.is_none()).then(|| quote!(#[track_caller]));
+    let value = construct(member);
+    quote! {
+        #cfg
+        impl ::core::default::Default for #enum_ident {
+            #track_caller
+            fn default() -> Self {
+                #bindings
+                #value
+            }
+        }
+    }
+}
+
+// `FooError::io_error(source)`, a constructor per variant named after it in snake case.
+// Const where nothing is called, without a mapper, locations or an opaque struct.
+pub(crate) fn variant_constructors(
+    enum_ident: &Ident,
+    error_set: &[ErrorMember],
+    config: &ComposeConfig,
+    construct: impl Fn(&ErrorMember) -> TokenStream2,
+) -> TokenStream2 {
+    if !config.constructors {
+        return TokenStream2::new();
+    }
+    // Methods generated on every enum, plus the optional ones
+    let taken = [
+        "kind",
+        "is_retryable",
+        "severity",
+        "get",
+        "chain",
+        "as_dyn_error",
+        "into_boxed",
+        "report",
+        "code",
+        "visit",
+        "exit_code",
+        "hint",
+        "user_message",
+        "to_ffi",
+        "variant_name",
+        "from_dyn",
+    ];
+    let constructors = error_set.iter().map(|member| {
+        let cfg = member.cfg_attr();
+        let variant = &member.variant;
+        let name = pascal_to_snake(&variant.to_string());
+        if taken.contains(&name.as_str()) {
+            return syn::Error::new(
+                variant.span(),
+                format!("the `{name}` constructor of this variant clashes with a method of the enum, rename the variant"),
+            )
+            .to_compile_error();
+        }
+        let constructor = match syn::parse_str::<Ident>(&name) {
+            Ok(_) => Ident::new(&name, variant.span()),
+            Err(_) => Ident::new_raw(&name, variant.span()),
+        };
+        let (params, calls) = match &member.inline {
+            Some(inline) => {
+                let names = inline.field_names();
+                let types = inline.fields.iter().flat_map(|fields| &fields.named).map(|field| &field.ty);
+                (quote!(#(#names: #types),*), false)
+            }
+            None => {
+                let ty = &member.ty;
+                (
+                    quote!(source: #ty),
+                    member.mapper.is_some() || config.track_caller,
+                )
+            }
+        };
+        let constness = (!calls && config.opaque.is_none()).then(|| quote!(const));
+        let track_caller = (config.track_caller && member.inline.is_none()).then(|| quote!(#[track_caller]));
+        let value = construct(member);
+        quote! {
+            #cfg
+            #[doc = concat!("Builds the `", stringify!(#variant), "` variant.")]
+            #track_caller
+            pub #constness fn #constructor(#params) -> Self {
+                #value
+            }
+        }
+    });
+    quote! {
+        impl #enum_ident {
+            #(#constructors)*
+        }
+    }
+}
+
+impl From<Ident> for ErrorMember {
+    fn from(ident: Ident) -> Self {
+        let ty = Type::Path(syn::TypePath {
+            qself: None,
+            path: ident.clone().into(),
+        });
+        ErrorMember::new(ident, ty)
+    }
+}
@@ -0,0 +1,230 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_quote, GenericArgument, Ident, ItemTrait, PathArguments, ReturnType, Signature,
+    TraitItem, Type,
+};
+
+use crate::name_trait_mock;
+
+// Test double of a trait, `MockFoo` for `trait Foo`. Each required method returns what the
+// closure programmed with `on_method` does, arguments are ignored. Methods that borrow in
+// their output, mention `Self`, are generic or take no `self` can't be programmed, and panic
+// with the reason when called, or when their future or iterator is first used.
+pub(crate) fn compose_trait_mock(trait_def: &ItemTrait) -> TokenStream2 {
+    let trait_ident = &trait_def.ident;
+    if !trait_def.generics.params.is_empty()
+        || trait_def
+            .items
+            .iter()
+            .any(|item| matches!(item, TraitItem::Type(_) | TraitItem::Const(_)))
+    {
+        return syn::Error::new(
+            trait_ident.span(),
+            "mock can't be generated for traits with generics, associated types or consts",
+        )
+        .to_compile_error();
+    }
+    let vis = &trait_def.vis;
+    let mock_ident = name_trait_mock(trait_ident);
+    let required = trait_def.items.iter().filter_map(|item| match item {
+        TraitItem::Fn(item_fn) if item_fn.default.is_none() => Some(&item_fn.sig),
+        _ => None,
+    });
+    let (programmable, fixed): (Vec<&Signature>, Vec<&Signature>) = required.partition(|sig| {
+        sig.receiver().is_some()
+            && sig.generics.params.is_empty()
+            && mock_return(&sig.output)
+                .is_some_and(|output| !borrows_or_mentions_self(output.programmed))
+    });
+    let outputs: Vec<TokenStream2> = programmable
+        .iter()
+        .filter_map(|sig| Some(mock_return(&sig.output)?.programmed))
+        .collect();
+    let returned = programmable
+        .iter()
+        .filter_map(|sig| Some((mock_return(&sig.output)?.returned)(quote!(returned))));
+    let fixed_bodies = fixed.iter().map(|sig| {
+        let name = &sig.ident;
+        let reason = if sig.receiver().is_none() {
+            "it takes no `self`"
+        } else if !sig.generics.params.is_empty() {
+            "it is generic"
+        } else {
+            "its output borrows or mentions `Self`"
+        };
+        let panic = quote! {
+            panic!(concat!(
+                stringify!(#mock_ident), "::", stringify!(#name), " can't be programmed, ", #reason
+            ))
+        };
+        match mock_return(&sig.output) {
+            Some(output) => (output.unprogrammed)(panic),
+            None => syn::Error::new_spanned(
+                &sig.output,
+                "mock can't return this `impl Trait`, only `impl Future` and `impl Iterator`",
+            )
+            .to_compile_error(),
+        }
+    });
+    let fields: Vec<&Ident> = programmable.iter().map(|sig| &sig.ident).collect();
+    let setters = fields
+        .iter()
+        .map(|field| format_ident!("on_{}", field.to_string().trim_start_matches("r#")));
+    let programmable_sigs = programmable.iter().map(|sig| ignore_arguments(sig));
+    let fixed_sigs = fixed.iter().map(|sig| ignore_arguments(sig));
+
+    quote! {
+        /// Test double of
+        #[doc = concat!("[`", stringify!(#trait_ident), "`],")]
+        /// each method returning what the closure programmed for it does.
+        #[allow(clippy::type_complexity)]
+        #vis struct #mock_ident {
+            #(
+                #fields: ::std::sync::Mutex<
+                    Option<Box<dyn FnMut() -> #outputs + Send>>,
+                >,
+            )*
+        }
+
+        impl Default for #mock_ident {
+            fn default() -> Self {
+                #mock_ident {
+                    #(#fields: ::std::sync::Mutex::new(None),)*
+                }
+            }
+        }
+
+        impl #mock_ident {
+            /// A mock with no method programmed yet, calling one panics.
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            #(
+                #[doc = concat!("Programs `", stringify!(#fields), "`, calling the closure each time.")]
+                pub fn #setters(
+                    mut self,
+                    returns: impl FnMut() -> #outputs + Send + 'static,
+                ) -> Self {
+                    *self
+                        .#fields
+                        .get_mut()
+                        .unwrap_or_else(::std::sync::PoisonError::into_inner) = Some(Box::new(returns));
+                    self
+                }
+            )*
+        }
+
+        impl #trait_ident for #mock_ident {
+            #(
+                #programmable_sigs {
+                    let mut programmed = self
+                        .#fields
+                        .lock()
+                        .unwrap_or_else(::std::sync::PoisonError::into_inner);
+                    let returns = programmed.as_mut().expect(concat!(
+                        stringify!(#mock_ident), "::", stringify!(#fields), " called without being programmed"
+                    ));
+                    let returned = returns();
+                    #returned
+                }
+            )*
+            #(
+                #fixed_sigs {
+                    #fixed_bodies
+                }
+            )*
+        }
+    }
+}
+
+// How a mock method returns. Methods of the trait returning `impl Future<Output = T>` are
+// programmed with `T`, and ones returning `impl Iterator<Item = T>` with `Vec<T>`.
+pub(crate) struct MockReturn {
+    // The type the closure programmed returns
+    pub(crate) programmed: TokenStream2,
+    // The method's return value, from a value of the programmed type
+    pub(crate) returned: fn(TokenStream2) -> TokenStream2,
+    // The body of a method that can't be programmed, from the panic it reports
+    pub(crate) unprogrammed: fn(TokenStream2) -> TokenStream2,
+}
+
+// How a method of the output returns, None for opaque types other than futures and iterators
+pub(crate) fn mock_return(output: &ReturnType) -> Option<MockReturn> {
+    let ty = match output {
+        ReturnType::Default => {
+            return Some(MockReturn {
+                programmed: quote!(()),
+                returned: |value| value,
+                unprogrammed: |panic| panic,
+            })
+        }
+        ReturnType::Type(_, ty) => ty,
+    };
+    if let Type::ImplTrait(impl_trait) = &**ty {
+        for bound in &impl_trait.bounds {
+            let syn::TypeParamBound::Trait(bound) = bound else {
+                continue;
+            };
+            let last = bound.path.segments.last().unwrap();
+            let PathArguments::AngleBracketed(type_args) = &last.arguments else {
+                continue;
+            };
+            let assoc = type_args.args.iter().find_map(|arg| match arg {
+                GenericArgument::AssocType(assoc) => Some(assoc),
+                _ => None,
+            });
+            match assoc {
+                Some(assoc) if last.ident == "Future" && assoc.ident == "Output" => {
+                    let inner = &assoc.ty;
+                    return Some(MockReturn {
+                        programmed: quote!(#inner),
+                        returned: |value| quote!(async move { #value }),
+                        unprogrammed: |panic| quote!(async move { #panic }),
+                    });
+                }
+                Some(assoc) if last.ident == "Iterator" && assoc.ident == "Item" => {
+                    let inner = &assoc.ty;
+                    return Some(MockReturn {
+                        programmed: quote!(::std::vec::Vec<#inner>),
+                        returned: |value| quote!(::std::iter::IntoIterator::into_iter(#value)),
+                        unprogrammed: |panic| quote!(::core::iter::from_fn(|| #panic)),
+                    });
+                }
+                _ => {}
+            }
+        }
+        return None;
+    }
+    Some(MockReturn {
+        programmed: quote!(#ty),
+        returned: |value| value,
+        unprogrammed: |panic| panic,
+    })
+}
+
+pub(crate) fn is_impl_trait_output(output: &ReturnType) -> bool {
+    matches!(output, ReturnType::Type(_, ty) if matches!(&**ty, Type::ImplTrait(_)))
+}
+
+// Whether the tokens hold a reference, lifetime, `impl Trait` or `Self`
+pub(crate) fn borrows_or_mentions_self(tokens: TokenStream2) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(ident) => ident == "Self" || ident == "impl",
+        proc_macro2::TokenTree::Punct(punct) => matches!(punct.as_char(), '&' | '\''),
+        proc_macro2::TokenTree::Group(group) => borrows_or_mentions_self(group.stream()),
+        proc_macro2::TokenTree::Literal(_) => false,
+    })
+}
+
+// The signature with each argument pattern replaced by `_`
+pub(crate) fn ignore_arguments(sig: &Signature) -> Signature {
+    let mut sig = sig.clone();
+    for input in &mut sig.inputs {
+        if let syn::FnArg::Typed(arg) = input {
+            *arg.pat = parse_quote!(_);
+        }
+    }
+    sig
+}
@@ -0,0 +1,220 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_quote, Attribute, Ident, ImplItem, Item, ReturnType, Signature, TraitItem, Type};
+
+use crate::{
+    has_inferred_error, is_errorset_attr, result_error_slots, split_on_commas, ComposeConfig,
+};
+
+// Places `#[compose_errors]`, with the module's arguments, on each function, trait, inherent
+// impl block and inline module inside it. Items with a `#[compose_errors]` of their own keep
+// it, and `#[compose_errors(skip)]` opts one out.
+pub(crate) fn compose_module(
+    module: &mut syn::ItemMod,
+    compose_args: TokenStream2,
+) -> TokenStream2 {
+    let Some((_, items)) = &mut module.content else {
+        return syn::Error::new_spanned(
+            &module.ident,
+            "compose_errors only applies to inline modules, `mod api { .. }`",
+        )
+        .to_compile_error();
+    };
+    let is_compose_attr = |attr: &Attribute| {
+        attr.path()
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "compose_errors")
+    };
+    for item in items {
+        let (attrs, composable) = match item {
+            Item::Fn(item) => (&mut item.attrs, true),
+            Item::Trait(item) => (&mut item.attrs, true),
+            Item::Impl(item) => (&mut item.attrs, item.trait_.is_none()),
+            Item::Mod(item) => (&mut item.attrs, item.content.is_some()),
+            _ => continue,
+        };
+        let is_skip = |attr: &Attribute| {
+            is_compose_attr(attr)
+                && matches!(&attr.meta, syn::Meta::List(list) if list.tokens.to_string() == "skip")
+        };
+        if attrs.iter().any(is_skip) {
+            attrs.retain(|attr| !is_compose_attr(attr));
+        } else if composable && !attrs.iter().any(is_compose_attr) {
+            attrs.insert(
+                0,
+                parse_quote!(#[::composerr::compose_errors(#compose_args)]),
+            );
+        }
+    }
+    quote!(#module)
+}
+
+// Methods of a scope with a `default_errorset` returning `Result<T, _>` get it as their
+// errorset when they have none, and `#[errorset(skip)]` leaves one alone. Errorsets with
+// `+Member` or `-Member` entries are the default with those added or removed.
+pub(crate) fn apply_default_errorset(ast: &mut Item, config: &ComposeConfig) -> TokenStream2 {
+    let Some(default) = &config.default_errorset else {
+        return TokenStream2::new();
+    };
+    let mut errors = TokenStream2::new();
+    let functions: Vec<(&mut Vec<Attribute>, &Signature)> = match ast {
+        Item::Trait(trait_def) => trait_def
+            .items
+            .iter_mut()
+            .filter_map(|item| match item {
+                TraitItem::Fn(item) => Some((&mut item.attrs, &item.sig)),
+                _ => None,
+            })
+            .collect(),
+        Item::Impl(impl_block) => impl_block
+            .items
+            .iter_mut()
+            .filter_map(|item| match item {
+                ImplItem::Fn(item) => Some((&mut item.attrs, &item.sig)),
+                _ => None,
+            })
+            .collect(),
+        Item::Fn(function) => vec![(&mut function.attrs, &function.sig)],
+        _ => Vec::new(),
+    };
+    for (attrs, sig) in functions {
+        let is_skip = |attr: &Attribute| {
+            is_errorset_attr(attr)
+                && matches!(&attr.meta, syn::Meta::List(list) if list.tokens.to_string() == "skip")
+        };
+        if attrs.iter().any(is_skip) {
+            attrs.retain(|attr| !is_errorset_attr(attr));
+        } else if !attrs.iter().any(is_errorset_attr) && has_inferred_error(sig) {
+            attrs.push(parse_quote!(#[errorset(#default)]));
+        } else if attrs.iter().any(is_relative_errorset) {
+            let mut entries = split_on_commas(default.clone());
+            for attr in attrs.iter().filter(|attr| is_errorset_attr(attr)) {
+                let syn::Meta::List(list) = &attr.meta else {
+                    continue;
+                };
+                for entry in split_on_commas(list.tokens.clone()) {
+                    let mut tokens = entry.clone().into_iter();
+                    match tokens.next() {
+                        Some(proc_macro2::TokenTree::Punct(sign)) if sign.as_char() == '+' => {
+                            entries.push(tokens.collect());
+                        }
+                        Some(proc_macro2::TokenTree::Punct(sign)) if sign.as_char() == '-' => {
+                            let removed = match (tokens.next(), tokens.next()) {
+                                (Some(proc_macro2::TokenTree::Ident(variant)), None) => variant,
+                                _ => {
+                                    errors.extend(
+                                        syn::Error::new_spanned(
+                                            &entry,
+                                            "members are removed by their variant, `-ConfigError`",
+                                        )
+                                        .to_compile_error(),
+                                    );
+                                    continue;
+                                }
+                            };
+                            let before = entries.len();
+                            entries.retain(|entry| {
+                                errorset_entry_variant(entry).as_ref() != Some(&removed)
+                            });
+                            if entries.len() == before {
+                                errors.extend(
+                                    syn::Error::new(
+                                        removed.span(),
+                                        format!(
+                                            "`{removed}` isn't a member of the default_errorset"
+                                        ),
+                                    )
+                                    .to_compile_error(),
+                                );
+                            }
+                        }
+                        _ => entries.push(entry),
+                    }
+                }
+            }
+            attrs.retain(|attr| !is_errorset_attr(attr));
+            attrs.push(parse_quote!(#[errorset(#(#entries),*)]));
+        }
+    }
+    errors
+}
+
+// Whether an errorset has `+Member` or `-Member` entries, relative to the default_errorset
+pub(crate) fn is_relative_errorset(attr: &Attribute) -> bool {
+    let syn::Meta::List(list) = &attr.meta else {
+        return false;
+    };
+    is_errorset_attr(attr)
+        && split_on_commas(list.tokens.clone()).iter().any(|entry| {
+            matches!(
+                entry.clone().into_iter().next(),
+                Some(proc_macro2::TokenTree::Punct(sign)) if sign.as_char() == '+' || sign.as_char() == '-'
+            )
+        })
+}
+
+// The variant an errorset entry declares, past `default` and `cfg(..)`
+pub(crate) fn errorset_entry_variant(entry: &TokenStream2) -> Option<Ident> {
+    let mut tokens = entry.clone().into_iter();
+    while let Some(tree) = tokens.next() {
+        match tree {
+            proc_macro2::TokenTree::Ident(ident) if ident == "default" => {}
+            proc_macro2::TokenTree::Ident(ident) if ident == "cfg" => {
+                tokens.next();
+            }
+            proc_macro2::TokenTree::Ident(ident) => return Some(ident),
+            _ => return None,
+        }
+    }
+    None
+}
+
+// Functions of the scope returning `Result<T, _>` with no errorset to fill in the `_`,
+// which would otherwise fail later as a placeholder in a signature
+pub(crate) fn unannotated_results(ast: &Item) -> TokenStream2 {
+    let functions: Vec<(&Signature, &[Attribute])> = match ast {
+        Item::Trait(trait_def) => trait_def
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                TraitItem::Fn(item) => Some((&item.sig, &item.attrs[..])),
+                _ => None,
+            })
+            .collect(),
+        Item::Impl(impl_block) => impl_block
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                ImplItem::Fn(item) => Some((&item.sig, &item.attrs[..])),
+                _ => None,
+            })
+            .collect(),
+        Item::Fn(function) => vec![(&function.sig, &function.attrs[..])],
+        _ => Vec::new(),
+    };
+    let errors = functions
+        .into_iter()
+        .filter(|(_, attrs)| !attrs.iter().any(is_errorset_attr))
+        .filter_map(|(sig, _)| {
+            let ReturnType::Type(_, return_type) = &sig.output else {
+                return None;
+            };
+            let mut return_type = (**return_type).clone();
+            let inferred = result_error_slots(&mut return_type)
+                .into_iter()
+                .any(|slot| matches!(slot, Type::Infer(_)));
+            inferred.then(|| {
+                syn::Error::new_spanned(
+                    return_type,
+                    format!(
+                        "the error of `{}` is only inferred with an errorset, \
+                        add `#[errorset(..)]` listing its errors",
+                        sig.ident
+                    ),
+                )
+                .to_compile_error()
+            })
+        });
+    quote!(#(#errors)*)
+}
@@ -0,0 +1,201 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::ToTokens;
+use syn::{GenericArgument, GenericParam, Ident, PathArguments};
+
+//
+pub(crate) fn name_composed_error(function_ident: &Ident, prefix: &str) -> Ident {
+    let name = format!(
+        "{}{}{}",
+        snake_to_pascal(prefix),
+        snake_to_pascal(&function_ident.to_string()),
+        "Error"
+    );
+    Ident::new(&name, function_ident.span())
+}
+
+pub(crate) fn name_error_kind(enum_ident: &Ident) -> Ident {
+    Ident::new(&format!("{}Kind", enum_ident), enum_ident.span())
+}
+
+pub(crate) fn name_error_context(enum_ident: &Ident) -> Ident {
+    Ident::new(&format!("{}Context", enum_ident), enum_ident.span())
+}
+
+pub(crate) fn name_ffi_code(enum_ident: &Ident) -> Ident {
+    Ident::new(&format!("{}FfiCode", enum_ident), enum_ident.span())
+}
+
+// A path as written in an intra-doc link, `std::io::Error`
+pub(crate) fn doc_path(path: &syn::Path) -> String {
+    path.segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+// `FooError` gives `FooResult`
+pub(crate) fn name_result_alias(enum_ident: &Ident) -> Ident {
+    let enum_name = enum_ident.to_string();
+    let base = enum_name.strip_suffix("Error").unwrap_or(&enum_name);
+    Ident::new(&format!("{}Result", base), enum_ident.span())
+}
+
+pub(crate) fn name_error_visitor(enum_ident: &Ident) -> Ident {
+    Ident::new(&format!("{}Visitor", enum_ident), enum_ident.span())
+}
+
+pub(crate) fn name_bail_macros(enum_ident: &Ident) -> (Ident, Ident) {
+    let snake = pascal_to_snake(&enum_ident.to_string());
+    (
+        Ident::new(&format!("{snake}_bail"), enum_ident.span()),
+        Ident::new(&format!("{snake}_ensure"), enum_ident.span()),
+    )
+}
+
+pub(crate) fn name_match_macro(enum_ident: &Ident) -> Ident {
+    let name = format!("match_{}", pascal_to_snake(&enum_ident.to_string()));
+    Ident::new(&name, enum_ident.span())
+}
+
+pub(crate) fn name_error_widen(enum_ident: &Ident) -> Ident {
+    Ident::new(&format!("{}Widen", enum_ident), enum_ident.span())
+}
+
+pub(crate) fn name_sealed_module(enum_ident: &Ident) -> Ident {
+    let name = format!("__composerr_{}", pascal_to_snake(&enum_ident.to_string()));
+    Ident::new(&name, enum_ident.span())
+}
+
+// `path::to::Service` names the marker `path::to::ServiceErrors`
+pub(crate) fn name_scope_marker(scope: &syn::Path) -> syn::Path {
+    let mut marker = scope.clone();
+    let last = marker.segments.last_mut().unwrap();
+    last.ident = Ident::new(&format!("{}Errors", last.ident), last.ident.span());
+    marker
+}
+
+pub(crate) fn name_trait_mock(trait_ident: &Ident) -> Ident {
+    Ident::new(&format!("Mock{trait_ident}"), trait_ident.span())
+}
+
+pub(crate) fn name_sealed_marker(enum_ident: &Ident) -> Ident {
+    Ident::new(&format!("{enum_ident}Sealed"), enum_ident.span())
+}
+
+pub(crate) fn name_export_macro() -> Ident {
+    Ident::new(
+        "__composerr_exported_errorset",
+        proc_macro2::Span::call_site(),
+    )
+}
+
+pub(crate) fn name_inherit_macro(trait_ident: &Ident) -> Ident {
+    let name = format!(
+        "__composerr_{}_errorsets",
+        pascal_to_snake(&trait_ident.to_string())
+    );
+    Ident::new(&name, trait_ident.span())
+}
+
+// The idents of concrete generic arguments in PascalCase, leaving out the parameters
+// of the impl, so `impl<T> Store<T>` keeps naming the plain `Store`
+pub(crate) fn name_type_arguments(arguments: &PathArguments, generics: &syn::Generics) -> String {
+    fn idents(tokens: TokenStream2, params: &[String], name: &mut String) {
+        let mut lifetime = false;
+        for tree in tokens {
+            match &tree {
+                proc_macro2::TokenTree::Group(group) => idents(group.stream(), params, name),
+                proc_macro2::TokenTree::Ident(ident) => {
+                    let ident = ident.to_string();
+                    if !lifetime && !params.contains(&ident) && ident != "dyn" && ident != "mut" {
+                        name.push_str(&snake_to_pascal(&ident));
+                    }
+                }
+                _ => {}
+            }
+            lifetime = matches!(&tree, proc_macro2::TokenTree::Punct(p) if p.as_char() == '\'');
+        }
+    }
+    let params: Vec<String> = generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(param) => Some(param.ident.to_string()),
+            GenericParam::Const(param) => Some(param.ident.to_string()),
+            GenericParam::Lifetime(_) => None,
+        })
+        .collect();
+    let PathArguments::AngleBracketed(arguments) = arguments else {
+        return String::new();
+    };
+    let mut name = String::new();
+    for argument in &arguments.args {
+        if let GenericArgument::Type(ty) = argument {
+            idents(ty.to_token_stream(), &params, &mut name);
+        }
+    }
+    name
+}
+
+// The name within a few edits of the misspelled one, if any
+pub(crate) fn closest_name<'a>(misspelled: &str, names: &[&'a str]) -> Option<&'a str> {
+    let distance = |a: &str, b: &str| {
+        let b: Vec<char> = b.chars().collect();
+        let mut row: Vec<usize> = (0..=b.len()).collect();
+        for (i, a) in a.chars().enumerate() {
+            let mut diagonal = row[0];
+            row[0] = i + 1;
+            for (j, b) in b.iter().enumerate() {
+                let substituted = diagonal + usize::from(a != *b);
+                diagonal = row[j + 1];
+                row[j + 1] = substituted.min(row[j] + 1).min(diagonal + 1);
+            }
+        }
+        row[b.len()]
+    };
+    names
+        .iter()
+        .map(|name| (distance(misspelled, name), *name))
+        .filter(|(distance, name)| *distance <= name.len().div_ceil(3))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
+pub(crate) fn pascal_to_snake(pascal_case: &str) -> String {
+    let mut snake_case = String::new();
+    for (i, c) in pascal_case.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i != 0 {
+                snake_case.push('_');
+            }
+            snake_case.push(c.to_ascii_lowercase());
+        } else {
+            snake_case.push(c);
+        }
+    }
+    snake_case
+}
+
+pub(crate) fn snake_to_pascal(snake_case: &str) -> String {
+    // Split the snake_case string into words
+    let words: Vec<&str> = snake_case.split('_').collect();
+
+    // Convert each word to PascalCase
+    let pascal_words: Vec<String> = words
+        .into_iter()
+        .map(|word| {
+            if word.is_empty() {
+                return String::new();
+            }
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => format!("{}", first.to_ascii_uppercase()) + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect();
+
+    // Join the words back together without any separators
+    pascal_words.join("")
+}
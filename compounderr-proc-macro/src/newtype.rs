@@ -0,0 +1,105 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::Ident;
+
+use crate::{name_result_alias, ComposeConfig, EnumSpec, ErrorMember};
+
+// A struct transparently wrapping the single member of a set, `FooError(pub IoError)`, in
+// place of a one-variant enum. It displays as the member, derefs to it and converts from it,
+// none of the enum's helpers are generated.
+pub(crate) fn compose_newtype_error(
+    ident: &Ident,
+    error_set: &[ErrorMember],
+    config: &ComposeConfig,
+) -> TokenStream2 {
+    let member = match error_set {
+        [member] => member,
+        _ => {
+            return syn::Error::new(
+                ident.span(),
+                "errorset(newtype) wraps a single member, list exactly one",
+            )
+            .to_compile_error()
+        }
+    };
+    if member.inline.is_some()
+        || member.route.is_some()
+        || member.mapper.is_some()
+        || member.dyn_trait.is_some()
+        || member.cfg.is_some()
+    {
+        return syn::Error::new(
+            member.variant.span(),
+            "errorset(newtype) wraps the member's type as it is, not inline, routed, mapped, \
+            `dyn` or conditional",
+        )
+        .to_compile_error();
+    }
+    if let Err(err) = config.validate_newtype(ident.span()) {
+        return err.to_compile_error();
+    }
+    let vis = &config.vis;
+    let ty = &member.ty;
+    let docs = EnumSpec {
+        ident,
+        members: error_set,
+        config,
+    }
+    .docs();
+    let wrapped = config.reported(quote!(#ident(error)));
+    let user_derives = config.user_derives();
+    let result_alias = config.result_alias.then(|| {
+        let alias_ident = name_result_alias(ident);
+        quote! {
+            #[doc = concat!("Result with a [`", stringify!(#ident), "`] error.")]
+            #vis type #alias_ident<T> = ::core::result::Result<T, #ident>;
+        }
+    });
+    quote! {
+        #docs
+        #[derive(Debug)]
+        #user_derives
+        #vis struct #ident(pub #ty);
+
+        impl ::core::fmt::Display for #ident {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::core::fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl ::core::error::Error for #ident {
+            fn source(&self) -> Option<&(dyn ::core::error::Error + 'static)> {
+                ::core::error::Error::source(&self.0)
+            }
+        }
+
+        impl From<#ty> for #ident {
+            fn from(error: #ty) -> Self {
+                #wrapped
+            }
+        }
+
+        impl From<::core::convert::Infallible> for #ident {
+            fn from(never: ::core::convert::Infallible) -> Self {
+                match never {}
+            }
+        }
+
+        impl ::core::ops::Deref for #ident {
+            type Target = #ty;
+
+            fn deref(&self) -> &#ty {
+                &self.0
+            }
+        }
+
+        impl #ident {
+            /// The member error, unwrapped.
+            pub fn into_inner(self) -> #ty {
+                self.0
+            }
+        }
+
+        #result_alias
+    }
+}
@@ -0,0 +1,171 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::Ident;
+
+use crate::{
+    default_impl, features, name_error_kind, routed_from_impls, shared_constructors,
+    variant_constructors, ComposeConfig, EnumSpec, ErrorMember,
+};
+
+// Stand-in for a composed enum under its `opaque(..)` predicate, only telling the kind of
+// error apart.
+// Members are dropped on conversion, so no payloads or their formatting end up in the binary.
+pub(crate) fn compose_opaque_error(
+    enum_ident: &Ident,
+    error_set: &[ErrorMember],
+    config: &ComposeConfig,
+) -> TokenStream2 {
+    let vis = &config.vis;
+    let kind_ident = name_error_kind(enum_ident);
+    let variants: Vec<&Ident> = error_set.iter().map(|member| &member.variant).collect();
+    let cfgs: Vec<TokenStream2> = error_set.iter().map(ErrorMember::cfg_attr).collect();
+    let spec = EnumSpec {
+        ident: enum_ident,
+        members: error_set,
+        config,
+    };
+    let (severity, _) = features::severity(&spec);
+    let into_boxed = config.boxed.then(|| {
+        quote! {
+            /// The error boxed as a trait object, for APIs taking `Box<dyn Error + Send + Sync>`.
+            pub fn into_boxed(self) -> ::std::boxed::Box<dyn ::core::error::Error + Send + Sync> {
+                ::std::boxed::Box::new(self)
+            }
+        }
+    });
+    // With `composed_trait`, the shared trait's methods find no member and no source either
+    let (access, composed_trait) = match &config.composed_trait {
+        Some(module) => (
+            TokenStream2::new(),
+            quote! {
+                impl #module::ComposedError for #enum_ident {
+                    fn member(&self) -> Option<&dyn ::core::any::Any> {
+                        None
+                    }
+                }
+            },
+        ),
+        None => (
+            quote! {
+                /// Always `None`, the inner error is not kept by the opaque struct.
+                pub fn get<E: 'static>(&self) -> Option<&E> {
+                    None
+                }
+
+                /// Iterates over the error alone, sources are not kept by the opaque struct.
+                pub fn chain(&self) -> impl Iterator<Item = &(dyn ::core::error::Error + 'static)> {
+                    ::core::iter::once(self as &(dyn ::core::error::Error + 'static))
+                }
+
+                /// The error as a trait object, for APIs taking `&dyn Error`.
+                pub fn as_dyn_error(&self) -> &(dyn ::core::error::Error + 'static) {
+                    self
+                }
+
+                #into_boxed
+            },
+            TokenStream2::new(),
+        ),
+    };
+    let wrapped: Vec<&ErrorMember> = error_set
+        .iter()
+        .filter(|member| member.inline.is_none() && member.route.is_none() && !member.shared)
+        .collect();
+    let types = wrapped.iter().map(|member| &member.ty);
+    let wrapped_cfgs = wrapped.iter().map(|member| member.cfg_attr());
+    let wrapped_values = wrapped.iter().map(|member| {
+        let variant = &member.variant;
+        config.reported(quote!(#enum_ident { kind: #kind_ident::#variant }))
+    });
+    let routed_from = routed_from_impls(enum_ident, error_set, TokenStream2::new(), |variant| {
+        config.reported(quote!(#enum_ident { kind: #kind_ident::#variant }))
+    });
+    let construct = |member: &ErrorMember| {
+        let variant = &member.variant;
+        let held = match &member.inline {
+            Some(inline) => {
+                let names = inline.field_names();
+                quote!(#(#names,)*)
+            }
+            None => quote!(source,),
+        };
+        quote!({
+            let _ = (#held);
+            #enum_ident { kind: #kind_ident::#variant }
+        })
+    };
+    let shared_constructors = shared_constructors(enum_ident, error_set, config, construct);
+    let variant_constructors = variant_constructors(enum_ident, error_set, config, construct);
+    let default_impl = default_impl(enum_ident, error_set, config, construct);
+    let variant_names = if config.variant_names {
+        let names = variants.iter().map(|variant| variant.to_string());
+        quote! {
+            impl #enum_ident {
+                /// Names of all the variants, in declaration order.
+                pub const VARIANTS: &'static [&'static str] = &[#(#cfgs #names),*];
+
+                /// Name of the variant held.
+                pub fn variant_name(&self) -> &'static str {
+                    self.kind.name()
+                }
+            }
+        }
+    } else {
+        TokenStream2::new()
+    };
+
+    let defmt_derive = config.defmt_derive();
+
+    quote! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #defmt_derive
+        #vis struct #enum_ident {
+            kind: #kind_ident,
+        }
+
+        impl ::core::fmt::Display for #enum_ident {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                write!(f, "error code {}", self.code())
+            }
+        }
+
+        impl ::core::error::Error for #enum_ident {}
+
+        #(
+            #wrapped_cfgs
+            impl From<#types> for #enum_ident {
+                fn from(_: #types) -> Self {
+                    #wrapped_values
+                }
+            }
+        )*
+
+        #routed_from
+
+        #shared_constructors
+
+        #variant_constructors
+
+        #default_impl
+
+        #variant_names
+
+        impl #enum_ident {
+            /// The kind of error held, as a fieldless enum.
+            pub fn kind(&self) -> #kind_ident {
+                self.kind
+            }
+
+            /// Whether the operation may succeed if retried, members are annotated `retryable`.
+            pub fn is_retryable(&self) -> bool {
+                self.kind.is_retryable()
+            }
+
+            #severity
+
+            #access
+        }
+
+        #composed_trait
+    }
+}
@@ -0,0 +1,516 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse_quote, Block, GenericParam, Ident, ImplItem, Item, ItemFn, ItemImpl, ItemTrait,
+    ReturnType, Signature, Stmt, TraitItem, Type,
+};
+
+use crate::{
+    errorset_args, is_errorset_attr, mentions_ident, name_type_arguments, replace_func_output,
+    result_error_slots, substitute_generic_error, ComposeConfig, ErrorsetArgs, FuncErrors,
+};
+
+pub(crate) type ScopeFuncs = (String, Vec<FuncErrors>);
+
+pub(crate) fn process_trait_def(trait_def: &mut ItemTrait, config: &ComposeConfig) -> ScopeFuncs {
+    // For a trait, use the trait name as the enum name
+    let scope_name = config.scope_name_or(trait_def.ident.to_string() + "Trait");
+    let mut functions = extract_trait_functions(trait_def);
+    link_scope_origins(&mut functions, &trait_def.ident);
+    report_scope_generics(&mut functions, &trait_def.generics);
+    strip_trait_functions_attrs(trait_def, &scope_name, config);
+    (scope_name, functions)
+}
+
+pub(crate) fn process_impl_block(impl_block: &mut ItemImpl, config: &ComposeConfig) -> ScopeFuncs {
+    // For an implementation, use the type name as the enum name
+    let (ident, arguments) = match &*impl_block.self_ty {
+        syn::Type::Path(tp) => {
+            let last = tp.path.segments.last().unwrap();
+            (last.ident.clone(), &last.arguments)
+        }
+        _ => panic!("not supported tokens"),
+    };
+    // Several impl blocks of the type are told apart by their `block`, or else by
+    // their concrete generic arguments, `impl Store<Vec<u8>>` being `StoreVecU8Impl`
+    let block = match &config.block {
+        Some(block) => block.to_string(),
+        None => name_type_arguments(arguments, &impl_block.generics),
+    };
+    let scope_name = config.scope_name_or(format!("{ident}{block}Impl"));
+    // If it's an impl trait, then abort.
+    if impl_block.trait_.is_some() {
+        panic!("Use this macro on the trait definition, or with use_trait on the implementation.")
+    };
+
+    let mut functions = extract_impl_functions(impl_block);
+    link_scope_origins(&mut functions, &ident);
+    report_scope_generics(&mut functions, &impl_block.generics);
+    strip_impl_functions_attrs(impl_block, &scope_name, config);
+    (scope_name, functions)
+}
+
+pub(crate) fn process_bare_function(function: &mut ItemFn, config: &ComposeConfig) -> ScopeFuncs {
+    // For bare function, use it's own name as the scope name
+    let scope_name = config.scope_name_or("".to_owned());
+    let functions = extract_bare_function(function);
+    strip_bare_function_attrs(function, &scope_name, config);
+    (scope_name, functions)
+}
+
+// Fills in `Result<T, _>` in the methods of `impl Store for Disk` with the enums composed
+// for `Store`, named next to the trait as its own config does, from the same options
+pub(crate) fn use_trait_errors(impl_block: &mut ItemImpl, config: &ComposeConfig) -> TokenStream2 {
+    let Some((_, trait_path, _)) = &impl_block.trait_ else {
+        return syn::Error::new_spanned(
+            &impl_block.self_ty,
+            "use_trait only applies to implementations of a trait, `impl Trait for Type`",
+        )
+        .to_compile_error();
+    };
+    let mut prefix = trait_path.clone();
+    let trait_ident = prefix.segments.pop().unwrap().into_value().ident;
+    let scope_name = config.scope_name_or(trait_ident.to_string() + "Trait");
+    let mut errors = TokenStream2::new();
+    for item in &mut impl_block.items {
+        let ImplItem::Fn(item_fn) = item else {
+            continue;
+        };
+        if item_fn.attrs.iter().any(is_errorset_attr) {
+            errors.extend(
+                syn::Error::new_spanned(
+                    &item_fn.sig.ident,
+                    "errorsets belong on the trait, implementations take its enums",
+                )
+                .to_compile_error(),
+            );
+            continue;
+        }
+        let enum_ident = config.name_enum_for(&item_fn.sig.ident, &scope_name, None);
+        let Type::Path(mut enum_type) = config.enum_type(&enum_ident) else {
+            unreachable!("enum types are paths");
+        };
+        for segment in prefix.segments.iter().rev() {
+            enum_type
+                .path
+                .segments
+                .insert(0, segment.ident.clone().into());
+        }
+        enum_type.path.leading_colon = prefix.leading_colon;
+        replace_func_output(&mut item_fn.sig.output, &Type::Path(enum_type), false);
+    }
+    errors
+}
+
+// Methods are linked through their trait or type, `Foo::load`
+pub(crate) fn link_scope_origins(functions: &mut [FuncErrors], scope_ident: &Ident) {
+    for func in functions {
+        let name = &func.name;
+        func.origin = Some(parse_quote!(#scope_ident::#name));
+    }
+}
+
+// The composed enums aren't generic, so members can't name the type or const parameters
+// of the trait or impl block. Each such member is reported, rather than left undefined.
+pub(crate) fn report_scope_generics(functions: &mut [FuncErrors], generics: &syn::Generics) {
+    let params: Vec<&Ident> = generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(param) => Some(&param.ident),
+            GenericParam::Const(param) => Some(&param.ident),
+            GenericParam::Lifetime(_) => None,
+        })
+        .collect();
+    for func in functions {
+        for member in func
+            .error_set
+            .iter()
+            .filter(|member| member.inline.is_none())
+        {
+            let ty = &member.ty;
+            let Some(param) = params
+                .iter()
+                .find(|param| mentions_ident(quote!(#ty), param))
+            else {
+                continue;
+            };
+            let error = syn::Error::new(
+                member.variant.span(),
+                format!(
+                    "errorset members can't use the generic parameter `{param}`, \
+                    the composed enum isn't generic"
+                ),
+            );
+            match &mut func.diagnostics {
+                Some(diagnostics) => diagnostics.combine(error),
+                None => func.diagnostics = Some(error),
+            }
+        }
+    }
+}
+
+pub(crate) fn extract_trait_functions(trait_def: &ItemTrait) -> Vec<FuncErrors> {
+    trait_def
+        .items
+        .iter()
+        // We want only function items
+        .filter_map(|item| match item {
+            TraitItem::Fn(item) => Some(item),
+            _ => None,
+        })
+        // and only those functions with #[errorset] attribute
+        .filter_map(|item| {
+            let args = errorset_args(&item.attrs)?;
+            Some(FuncErrors::new(
+                &item.sig,
+                &item.attrs,
+                item.default.as_ref(),
+                args,
+            ))
+        })
+        .collect()
+}
+
+pub(crate) fn extract_impl_functions(impl_block: &ItemImpl) -> Vec<FuncErrors> {
+    impl_block
+        .items
+        .iter()
+        // We want only function items
+        .filter_map(|item| match item {
+            ImplItem::Fn(item) => Some(item),
+            _ => None,
+        })
+        // and only those functions with #[errorset] attribute
+        .filter_map(|item| {
+            let args = errorset_args(&item.attrs)?;
+            Some(FuncErrors::new(
+                &item.sig,
+                &item.attrs,
+                Some(&item.block),
+                args,
+            ))
+        })
+        .collect()
+}
+
+pub(crate) fn extract_bare_function(function: &ItemFn) -> Vec<FuncErrors> {
+    match errorset_args(&function.attrs) {
+        Some(args) => vec![FuncErrors::new(
+            &function.sig,
+            &function.attrs,
+            Some(&function.block),
+            args,
+        )],
+        None => vec![],
+    }
+}
+
+// Mutates ItemTrait in place. Removing the #[errorset] helper attribute
+// Also changes the return Result type, installing the custom composed error.
+pub(crate) fn strip_trait_functions_attrs(
+    trait_def: &mut ItemTrait,
+    scope_name: &str,
+    config: &ComposeConfig,
+) {
+    for item in &mut trait_def.items {
+        let TraitItem::Fn(item_fn) = item else {
+            continue;
+        };
+        if let Some(args) = errorset_args(&item_fn.attrs) {
+            let enum_ident =
+                config.name_enum_for(&item_fn.sig.ident, scope_name, args.name.as_ref());
+            install_composed_error(
+                &mut item_fn.sig,
+                item_fn.default.as_mut(),
+                &config.signature_type(&enum_ident, args.within.as_ref()),
+                &args,
+            );
+        }
+        item_fn.attrs.retain(|attr| !is_errorset_attr(attr));
+    }
+}
+
+// Mutates ItemImpl in place. Removing the #[errorset] helper attribute
+// Also changes the return Result type, installing the custom composed error.
+pub(crate) fn strip_impl_functions_attrs(
+    impl_block: &mut ItemImpl,
+    scope_name: &str,
+    config: &ComposeConfig,
+) {
+    for item in &mut impl_block.items {
+        let ImplItem::Fn(item_fn) = item else {
+            continue;
+        };
+        if let Some(args) = errorset_args(&item_fn.attrs) {
+            let enum_ident =
+                config.name_enum_for(&item_fn.sig.ident, scope_name, args.name.as_ref());
+            install_composed_error(
+                &mut item_fn.sig,
+                Some(&mut item_fn.block),
+                &config.signature_type(&enum_ident, args.within.as_ref()),
+                &args,
+            );
+        }
+        item_fn.attrs.retain(|attr| !is_errorset_attr(attr));
+    }
+}
+
+// Mutates function in place. Removing the #[errorset] helper attribute
+// Also changes the return Result type, installing the custom composed error.
+// Only the output is touched, so qualifiers like const, unsafe and extern "ABI" are kept.
+pub(crate) fn strip_bare_function_attrs(
+    function: &mut ItemFn,
+    scope_name: &str,
+    config: &ComposeConfig,
+) {
+    if let Some(args) = errorset_args(&function.attrs) {
+        let enum_ident = config.name_enum_for(&function.sig.ident, scope_name, args.name.as_ref());
+        install_composed_error(
+            &mut function.sig,
+            Some(&mut function.block),
+            &config.signature_type(&enum_ident, args.within.as_ref()),
+            &args,
+        );
+    }
+    function.attrs.retain(|attr| !is_errorset_attr(attr));
+}
+
+// Adds `load_typed` next to each typed `load`, in its trait or impl. Bare functions get
+// theirs returned, to be emitted next to them. Signatures without a Result to keep are
+// reported with the function's diagnostics.
+pub(crate) fn add_typed_siblings(
+    ast: &mut Item,
+    functions: &mut [FuncErrors],
+    enum_type: impl Fn(&FuncErrors) -> Type,
+) -> TokenStream2 {
+    let mut bare = TokenStream2::new();
+    for func in functions.iter_mut().filter(|func| func.typed) {
+        let enum_type = enum_type(func);
+        let sibling = match &*ast {
+            Item::Trait(trait_def) => trait_def.items.iter().find_map(|item| match item {
+                TraitItem::Fn(item) if item.sig.ident == func.name => Some(typed_sibling(
+                    &item.sig,
+                    &syn::Visibility::Inherited,
+                    func,
+                    &enum_type,
+                    true,
+                )),
+                _ => None,
+            }),
+            Item::Impl(impl_block) => impl_block.items.iter().find_map(|item| match item {
+                ImplItem::Fn(item) if item.sig.ident == func.name => {
+                    Some(typed_sibling(&item.sig, &item.vis, func, &enum_type, true))
+                }
+                _ => None,
+            }),
+            Item::Fn(function) => Some(typed_sibling(
+                &function.sig,
+                &function.vis,
+                func,
+                &enum_type,
+                false,
+            )),
+            _ => None,
+        };
+        let sibling = match sibling {
+            Some(Ok(sibling)) => sibling,
+            Some(Err(error)) => {
+                match &mut func.diagnostics {
+                    Some(diagnostics) => diagnostics.combine(error),
+                    None => func.diagnostics = Some(error),
+                }
+                continue;
+            }
+            None => continue,
+        };
+        let cfgs = &func.cfgs;
+        match ast {
+            Item::Trait(trait_def) => trait_def.items.push(parse_quote!(#(#cfgs)* #sibling)),
+            Item::Impl(impl_block) => impl_block.items.push(parse_quote!(#(#cfgs)* #sibling)),
+            _ => bare = quote!(#(#cfgs)* #sibling),
+        }
+    }
+    bare
+}
+
+// `fn load_typed(..) -> Result<T, LoadError>`, calling `load` and downcasting its boxed
+// error to each member in turn. What isn't a member is kept boxed, in `Other`.
+pub(crate) fn typed_sibling(
+    sig: &Signature,
+    vis: &syn::Visibility,
+    func: &FuncErrors,
+    enum_type: &Type,
+    in_scope: bool,
+) -> syn::Result<TokenStream2> {
+    let mut typed_sig = sig.clone();
+    typed_sig.ident = format_ident!("{}_typed", sig.ident);
+    let mut args = Vec::new();
+    for (index, input) in typed_sig.inputs.iter_mut().enumerate() {
+        match input {
+            syn::FnArg::Receiver(_) => args.push(quote!(self)),
+            syn::FnArg::Typed(pat_type) => {
+                let arg = format_ident!("arg{}", index);
+                *pat_type.pat = parse_quote!(#arg);
+                args.push(quote!(#arg));
+            }
+        }
+    }
+    let message = "errorset(typed) needs a `Result<T, Box<dyn Error>>` return type";
+    let ReturnType::Type(_, return_type) = &mut typed_sig.output else {
+        return Err(syn::Error::new(sig.ident.span(), message));
+    };
+    let Some(slot) = result_error_slots(return_type).into_iter().next() else {
+        return Err(syn::Error::new_spanned(&sig.output, message));
+    };
+    let boxed = std::mem::replace(slot, enum_type.clone());
+    // Generic arguments are given explicitly, unless `impl Trait` arguments forbid it
+    let generics = sig.generics.params.iter().filter_map(|param| match param {
+        GenericParam::Type(param) => Some(&param.ident),
+        GenericParam::Const(param) => Some(&param.ident),
+        GenericParam::Lifetime(_) => None,
+    });
+    let generics: Vec<&Ident> = generics.collect();
+    let inputs = &sig.inputs;
+    let turbofish = (!generics.is_empty() && !quote!(#inputs).to_string().contains("impl"))
+        .then(|| quote!(::<#(#generics),*>));
+    let ident = &sig.ident;
+    let scope = in_scope.then(|| quote!(Self::));
+    let awaited = sig.asyncness.map(|_| quote!(.await));
+    let call = quote!(#scope #ident #turbofish(#(#args),*) #awaited);
+    // Each member type once, routed and shared ones converting through their From impl
+    let mut types: Vec<&Type> = Vec::new();
+    for member in &func.error_set {
+        if member.inline.is_none()
+            && member.dyn_trait.is_none()
+            && !member.shared
+            && !types
+                .iter()
+                .any(|ty| quote!(#ty).to_string() == member.ty.to_token_stream().to_string())
+        {
+            types.push(&member.ty);
+        }
+    }
+    let doc = format!(
+        "Like [`{ident}`]({}{ident}), with the error as [`{}`].",
+        if in_scope { "Self::" } else { "" },
+        quote!(#enum_type).to_string().replace(' ', "")
+    );
+    Ok(quote! {
+        #[doc = #doc]
+        #vis #typed_sig {
+            match #call {
+                ::core::result::Result::Ok(value) => ::core::result::Result::Ok(value),
+                ::core::result::Result::Err(error) => {
+                    #(
+                        let error = match error.downcast::<#types>() {
+                            ::core::result::Result::Ok(member) => {
+                                return ::core::result::Result::Err(<#enum_type as From<#types>>::from(*member));
+                            }
+                            ::core::result::Result::Err(error) => error,
+                        };
+                    )*
+                    ::core::result::Result::Err(<#enum_type as From<#boxed>>::from(error))
+                }
+            }
+        }
+    })
+}
+
+// Installs the composed error in the signature. A generic error parameter, as in
+// `fn run<E: Into<MyError>>() -> Result<(), E>`, is substituted away with its bounds,
+// and aliased at the top of the body so `E` still names the error there.
+// With io_with_context, the `ResultExt` helper is declared at the top of the body too.
+pub(crate) fn install_composed_error(
+    sig: &mut Signature,
+    mut body: Option<&mut Block>,
+    composed_error_type: &Type,
+    args: &ErrorsetArgs,
+) {
+    // Left as written, the typed sibling is added with the rest of the scope
+    if args.typed.is_some() {
+        return;
+    }
+    #[cfg(feature = "body-analysis")]
+    if args.throws && !crate::is_impl_trait_output(&sig.output) {
+        crate::body::wrap_throws_output(&mut sig.output);
+        if let Some(body) = body.as_deref_mut() {
+            crate::body::wrap_throws_body(body);
+        }
+    }
+    #[cfg(feature = "body-analysis")]
+    if let Some(body) = body.as_deref_mut() {
+        let members = args
+            .error_set(&sig.output)
+            .0
+            .iter()
+            .filter(|member| member.inline.is_none() && member.dyn_trait.is_none())
+            .filter_map(|member| match &member.ty {
+                Type::Path(tp) => Some(tp.path.segments.last().unwrap().ident.clone()),
+                _ => None,
+            })
+            .collect();
+        crate::body::convert_returned_members(body, members);
+    }
+    if args.io_with_context {
+        if let Some(body) = body.as_deref_mut() {
+            body.stmts.splice(0..0, io_result_ext(composed_error_type));
+        }
+    }
+    #[cfg(feature = "body-analysis")]
+    if args.map_err {
+        if let Some(body) = body.as_deref_mut() {
+            crate::body::convert_question_marks(body, composed_error_type);
+        }
+    }
+    #[cfg(feature = "body-analysis")]
+    if args.context {
+        if let (Some(body), Type::Path(error_type)) = (body.as_deref_mut(), composed_error_type) {
+            let mut context_trait = error_type.path.clone();
+            let last = context_trait.segments.last_mut().unwrap();
+            last.ident = crate::name_error_context(&last.ident);
+            crate::body::call_context_trait(body, context_trait);
+        }
+    }
+    if args.extend.is_none() {
+        if let Some(param) = substitute_generic_error(sig, composed_error_type) {
+            if let Some(body) = body {
+                let alias: syn::ItemType = parse_quote! {
+                    #[allow(dead_code)]
+                    type #param = #composed_error_type;
+                };
+                body.stmts.insert(0, Stmt::Item(Item::Type(alias)));
+            }
+        }
+    }
+    replace_func_output(&mut sig.output, composed_error_type, args.extend.is_some());
+}
+
+// `ResultExt::with_path`, attaching the operation and path to an io::Error, as fs_err does.
+// Declared in the function body, so the helpers of different functions do not clash.
+pub(crate) fn io_result_ext(composed_error_type: &Type) -> Vec<Stmt> {
+    let helper: Block = parse_quote! {{
+        #[allow(dead_code)]
+        trait ResultExt<T> {
+            fn with_path(
+                self,
+                op: &'static str,
+                path: impl AsRef<::std::path::Path>,
+            ) -> ::core::result::Result<T, #composed_error_type>;
+        }
+        impl<T> ResultExt<T> for ::core::result::Result<T, ::std::io::Error> {
+            fn with_path(
+                self,
+                op: &'static str,
+                path: impl AsRef<::std::path::Path>,
+            ) -> ::core::result::Result<T, #composed_error_type> {
+                self.map_err(|source| #composed_error_type::Io {
+                    source,
+                    path: path.as_ref().to_path_buf(),
+                    op,
+                })
+            }
+        }
+    }};
+    helper.stmts
+}
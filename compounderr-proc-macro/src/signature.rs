@@ -0,0 +1,359 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::{
+    parse_quote, GenericArgument, GenericParam, Ident, ImplItem, Item, PathArguments, ReturnType,
+    Signature, TraitItem, Type,
+};
+
+use crate::ComposeConfig;
+
+// When the outermost Result error is a type parameter of the function, removes the
+// parameter and its bounds, leaving `_` for the composed error. Returns the parameter.
+// Unless the arguments use it too, see `generic_error_conflict`.
+pub(crate) fn substitute_generic_error(
+    sig: &mut Signature,
+    composed_error_type: &Type,
+) -> Option<Ident> {
+    let param = generic_error_param(sig)?;
+    // Used by the arguments too, reported with the function's diagnostics
+    let inputs = &sig.inputs;
+    if mentions_ident(quote!(#inputs), &param) {
+        return None;
+    }
+    let ReturnType::Type(_, return_type) = &mut sig.output else {
+        return None;
+    };
+    *result_error_slots(return_type).into_iter().next()? = parse_quote!(_);
+
+    sig.generics.params = std::mem::take(&mut sig.generics.params)
+        .into_iter()
+        .filter(|generic| !matches!(generic, GenericParam::Type(type_param) if type_param.ident == param))
+        .collect();
+    if sig.generics.params.is_empty() {
+        sig.generics.lt_token = None;
+        sig.generics.gt_token = None;
+    }
+    if let Some(where_clause) = &mut sig.generics.where_clause {
+        where_clause.predicates = std::mem::take(&mut where_clause.predicates)
+            .into_iter()
+            .filter(|predicate| {
+                !matches!(predicate, syn::WherePredicate::Type(bound)
+                    if matches!(&bound.bounded_ty, Type::Path(ty) if ty.path.is_ident(&param)))
+            })
+            .collect();
+        if where_clause.predicates.is_empty() {
+            sig.generics.where_clause = None;
+        }
+    }
+    // Other bounds naming it, as `where T: Into<E>`, name the composed enum instead
+    let composed = composed_error_type.to_token_stream();
+    let params = &sig.generics.params;
+    let params = replace_ident(quote!(#params), &param, &composed);
+    sig.generics.params = parse_quote!(#params);
+    if let Some(where_clause) = &sig.generics.where_clause {
+        let where_clause = replace_ident(quote!(#where_clause), &param, &composed);
+        sig.generics.where_clause = Some(parse_quote!(#where_clause));
+    }
+    Some(param)
+}
+
+// The generic error parameter also taken by the arguments, which can't be substituted
+pub(crate) fn generic_error_conflict(sig: &Signature) -> Option<syn::Error> {
+    let param = generic_error_param(sig)?;
+    let input = sig
+        .inputs
+        .iter()
+        .find(|input| mentions_ident(quote!(#input), &param))?;
+    Some(syn::Error::new_spanned(
+        input,
+        format!(
+            "the generic error parameter `{param}` is also used by the arguments, \
+            it can't be substituted with the composed enum"
+        ),
+    ))
+}
+
+// The type parameter the signature returns as its error, `E` of `-> Result<(), E>`
+pub(crate) fn generic_error_param(sig: &Signature) -> Option<Ident> {
+    let existing = existing_result_error(&sig.output)?;
+    let Type::Path(error_type) = existing else {
+        return None;
+    };
+    let param = error_type.path.get_ident()?.clone();
+    sig.generics
+        .type_params()
+        .any(|type_param| type_param.ident == param)
+        .then_some(param)
+}
+
+// The tokens with each occurrence of the ident replaced
+pub(crate) fn replace_ident(
+    tokens: TokenStream2,
+    ident: &Ident,
+    replacement: &TokenStream2,
+) -> TokenStream2 {
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            proc_macro2::TokenTree::Ident(found) if &found == ident => replacement.clone(),
+            proc_macro2::TokenTree::Group(group) => {
+                let mut replaced = proc_macro2::Group::new(
+                    group.delimiter(),
+                    replace_ident(group.stream(), ident, replacement),
+                );
+                replaced.set_span(group.span());
+                proc_macro2::TokenTree::Group(replaced).into()
+            }
+            token => token.into(),
+        })
+        .collect()
+}
+
+// Whether the ident appears anywhere in the tokens
+pub(crate) fn mentions_ident(tokens: TokenStream2, ident: &Ident) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(found) => &found == ident,
+        proc_macro2::TokenTree::Group(group) => mentions_ident(group.stream(), ident),
+        _ => false,
+    })
+}
+
+// This function takes a mutable reference to function ReturnType and attempts to modify it.
+// Replaces the error variant if it is a Result type, or wraps one (e.g. `Option<Result<T, _>>`).
+// When extending, the existing error of the outermost Result is replaced instead.
+pub(crate) fn replace_func_output(
+    return_type: &mut ReturnType,
+    composed_error_type: &Type,
+    extend: bool,
+) {
+    if let ReturnType::Type(_, return_type) = return_type {
+        let composed_error_type = composed_error_type.clone();
+        let mut slots = result_error_slots(return_type);
+        if extend {
+            if let Some(slot) = slots.first_mut() {
+                **slot = composed_error_type;
+            }
+            return;
+        }
+        for slot in slots {
+            // Check if the error is of type inference
+            if let Type::Infer(_) = slot {
+                // Replace the inferred type with the composed error type
+                *slot = composed_error_type.clone();
+            }
+        }
+    }
+}
+
+// Whether the return type has a `Result<T, _>` for the composed error to fill in,
+// or will have once a generic error parameter is substituted
+pub(crate) fn has_inferred_error(sig: &Signature) -> bool {
+    if generic_error_param(sig).is_some() {
+        return true;
+    }
+    let ReturnType::Type(_, return_type) = &sig.output else {
+        return false;
+    };
+    let mut return_type = (**return_type).clone();
+    let has_inferred = result_error_slots(&mut return_type)
+        .into_iter()
+        .any(|slot| matches!(slot, Type::Infer(_)));
+    has_inferred
+}
+
+// The error type of the outermost Result in the return type, as written
+pub(crate) fn existing_result_error(return_type: &ReturnType) -> Option<Type> {
+    let ReturnType::Type(_, return_type) = return_type else {
+        return None;
+    };
+    let mut return_type = return_type.clone();
+    let existing = result_error_slots(&mut return_type)
+        .into_iter()
+        .next()
+        .map(|slot| slot.clone());
+    existing
+}
+
+// Whether the type is `Box<dyn Trait>`, by any path to `Box`
+pub(crate) fn is_boxed_trait_object(ty: &Type) -> bool {
+    let Type::Path(type_path) = ty else {
+        return false;
+    };
+    let last = type_path.path.segments.last().unwrap();
+    let PathArguments::AngleBracketed(args) = &last.arguments else {
+        return false;
+    };
+    last.ident == "Box"
+        && matches!(
+            args.args.first(),
+            Some(GenericArgument::Type(Type::TraitObject(_)))
+        )
+}
+
+// Recursively walks the type arguments of a type, collecting the error slot
+// of every `Result<T, E>` found along the way, outermost first. Tuple elements
+// are walked in order, `(Result<T, _>, Stats)`, and so are the bounds of
+// `impl Iterator<Item = Result<T, _>>` and `dyn` trait objects.
+pub(crate) fn result_error_slots(ty: &mut Type) -> Vec<&mut Type> {
+    match ty {
+        Type::Path(type_path) => path_error_slots(&mut type_path.path),
+        Type::Tuple(tuple) => tuple
+            .elems
+            .iter_mut()
+            .flat_map(result_error_slots)
+            .collect(),
+        Type::ImplTrait(impl_trait) => bound_error_slots(&mut impl_trait.bounds),
+        Type::TraitObject(trait_object) => bound_error_slots(&mut trait_object.bounds),
+        Type::Paren(paren) => result_error_slots(&mut paren.elem),
+        Type::Group(group) => result_error_slots(&mut group.elem),
+        Type::Array(array) => result_error_slots(&mut array.elem),
+        _ => Vec::new(),
+    }
+}
+
+// The error slots in the associated types of trait bounds, `Iterator<Item = Result<T, _>>`
+pub(crate) fn bound_error_slots(
+    bounds: &mut syn::punctuated::Punctuated<syn::TypeParamBound, syn::Token![+]>,
+) -> Vec<&mut Type> {
+    bounds
+        .iter_mut()
+        .flat_map(|bound| match bound {
+            syn::TypeParamBound::Trait(bound) => path_error_slots(&mut bound.path),
+            _ => Vec::new(),
+        })
+        .collect()
+}
+
+pub(crate) fn path_error_slots(path: &mut syn::Path) -> Vec<&mut Type> {
+    let is_result = is_result_path(path);
+    let PathArguments::AngleBracketed(type_args) = &mut path.segments.last_mut().unwrap().arguments
+    else {
+        return Vec::new();
+    };
+    // Result <T, E>
+    let is_result = is_result && type_args.args.len() == 2;
+    let mut slots = Vec::new();
+    let mut nested = Vec::new();
+    for (position, arg) in type_args.args.iter_mut().enumerate() {
+        match arg {
+            GenericArgument::Type(inner) => {
+                if is_result && position == 1 {
+                    slots.push(inner);
+                } else {
+                    // Look for Results nested deeper, like `Option<Result<T, _>>`
+                    nested.extend(result_error_slots(inner));
+                }
+            }
+            // and in associated types, like `Iterator<Item = Result<T, _>>`
+            GenericArgument::AssocType(assoc) => nested.extend(result_error_slots(&mut assoc.ty)),
+            _ => {}
+        }
+    }
+    slots.extend(nested);
+    slots
+}
+
+// `Result`, or spelled out as `std::result::Result` or `core::result::Result`
+pub(crate) fn is_result_path(path: &syn::Path) -> bool {
+    let idents: Vec<String> = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    match &idents[..] {
+        [result] => result == "Result" && path.leading_colon.is_none(),
+        [krate, module, result] => {
+            (krate == "std" || krate == "core") && module == "result" && result == "Result"
+        }
+        _ => false,
+    }
+}
+
+// Spells the `result_types` aliases of `Result<T, E>` in the return type as
+// `::core::result::Result`, for the error slot to be found like any other
+pub(crate) fn spell_result_aliases(ty: &mut Type, aliases: &[syn::Path]) {
+    match ty {
+        Type::Path(type_path) => spell_path_result_aliases(&mut type_path.path, aliases),
+        Type::Tuple(tuple) => {
+            for elem in tuple.elems.iter_mut() {
+                spell_result_aliases(elem, aliases);
+            }
+        }
+        Type::ImplTrait(impl_trait) => spell_bound_result_aliases(&mut impl_trait.bounds, aliases),
+        Type::TraitObject(trait_object) => {
+            spell_bound_result_aliases(&mut trait_object.bounds, aliases)
+        }
+        Type::Paren(paren) => spell_result_aliases(&mut paren.elem, aliases),
+        Type::Group(group) => spell_result_aliases(&mut group.elem, aliases),
+        Type::Array(array) => spell_result_aliases(&mut array.elem, aliases),
+        _ => {}
+    }
+}
+
+pub(crate) fn spell_bound_result_aliases(
+    bounds: &mut syn::punctuated::Punctuated<syn::TypeParamBound, syn::Token![+]>,
+    aliases: &[syn::Path],
+) {
+    for bound in bounds.iter_mut() {
+        if let syn::TypeParamBound::Trait(bound) = bound {
+            spell_path_result_aliases(&mut bound.path, aliases);
+        }
+    }
+}
+
+pub(crate) fn spell_path_result_aliases(path: &mut syn::Path, aliases: &[syn::Path]) {
+    let mut written = path.clone();
+    let PathArguments::AngleBracketed(mut type_args) =
+        written.segments.last_mut().unwrap().arguments.clone()
+    else {
+        return;
+    };
+    written.segments.last_mut().unwrap().arguments = PathArguments::None;
+    for arg in type_args.args.iter_mut() {
+        match arg {
+            GenericArgument::Type(inner) => spell_result_aliases(inner, aliases),
+            GenericArgument::AssocType(assoc) => spell_result_aliases(&mut assoc.ty, aliases),
+            _ => {}
+        }
+    }
+    let is_alias = type_args.args.len() == 2
+        && aliases
+            .iter()
+            .any(|alias| quote!(#alias).to_string() == quote!(#written).to_string());
+    if is_alias {
+        *path = parse_quote!(::core::result::Result);
+    }
+    path.segments.last_mut().unwrap().arguments = PathArguments::AngleBracketed(type_args);
+}
+
+// The return types of the functions of the item, with their `result_types` aliases spelled out
+pub(crate) fn spell_item_result_aliases(ast: &mut Item, config: &ComposeConfig) {
+    if config.result_types.is_empty() {
+        return;
+    }
+    let outputs: Vec<&mut ReturnType> = match ast {
+        Item::Trait(trait_def) => trait_def
+            .items
+            .iter_mut()
+            .filter_map(|item| match item {
+                TraitItem::Fn(item) => Some(&mut item.sig.output),
+                _ => None,
+            })
+            .collect(),
+        Item::Impl(impl_block) => impl_block
+            .items
+            .iter_mut()
+            .filter_map(|item| match item {
+                ImplItem::Fn(item) => Some(&mut item.sig.output),
+                _ => None,
+            })
+            .collect(),
+        Item::Fn(function) => vec![&mut function.sig.output],
+        _ => Vec::new(),
+    };
+    for output in outputs {
+        if let ReturnType::Type(_, return_type) = output {
+            spell_result_aliases(return_type, &config.result_types);
+        }
+    }
+}
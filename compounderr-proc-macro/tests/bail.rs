@@ -0,0 +1,65 @@
+// `bail_macros`, returning early with a member or the enum, into the function's own error.
+use composerr::compose_errors;
+use std::io::{Error as IoError, ErrorKind};
+
+#[compose_errors(bail_macros)]
+#[errorset(IoError, TooLarge { len: usize } : "{len} bytes is too large")]
+fn check(data: &[u8]) -> Result<usize, _> {
+    check_error_ensure!(data.len() < 4, CheckError::TooLarge { len: data.len() });
+    if data.is_empty() {
+        check_error_bail!(IoError::from(ErrorKind::UnexpectedEof));
+    }
+    Ok(data.len())
+}
+
+#[test]
+fn bail_and_ensure_return_early() {
+    assert_eq!(check(b"ab").unwrap(), 2);
+    let error = check(b"abcd").unwrap_err();
+    assert!(matches!(error, CheckError::TooLarge { len: 4 }));
+    assert_eq!(error.to_string(), "4 bytes is too large");
+    assert!(matches!(check(b""), Err(CheckError::IoError(_))));
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("nothing to save")]
+pub struct EmptyError;
+
+struct Store;
+
+// The error is converted into the enum, then into the wider error of a sibling
+#[compose_errors(bail_macros)]
+impl Store {
+    #[errorset(IoError, EmptyError)]
+    fn validate(&self, data: &[u8]) -> Result<(), _> {
+        store_impl_validate_error_ensure!(!data.is_empty(), EmptyError);
+        Ok(())
+    }
+
+    #[errorset(IoError, EmptyError, ReadOnly : "store is read only")]
+    fn save(&self, data: &[u8]) -> Result<(), _> {
+        if data.len() == 1 {
+            store_impl_validate_error_bail!(IoError::other("one byte"));
+        }
+        store_impl_save_error_ensure!(data.len() < 4, StoreImplSaveError::ReadOnly);
+        self.validate(data)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn into_a_wider_error() {
+    assert!(matches!(
+        Store.save(b"a"),
+        Err(StoreImplSaveError::IoError(_))
+    ));
+    assert!(matches!(
+        Store.save(b""),
+        Err(StoreImplSaveError::EmptyError(_))
+    ));
+    assert!(matches!(
+        Store.save(b"abcd"),
+        Err(StoreImplSaveError::ReadOnly)
+    ));
+    assert!(Store.save(b"ab").is_ok());
+}
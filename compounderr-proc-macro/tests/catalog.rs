@@ -0,0 +1,76 @@
+// `catalog`, the `ERROR_CATALOG` of a scope, with its own types or ones shared between scopes.
+use composerr::compose_errors;
+use std::{io::Error as IoError, num::ParseIntError};
+
+pub struct Store;
+
+#[compose_errors(catalog)]
+impl Store {
+    #[errorset(IoError(retryable), ParseIntError)]
+    fn load(&self) -> Result<u8, _> {
+        Ok("1".parse()?)
+    }
+
+    #[errorset(Full { used: u64 } : "store is full")]
+    fn save(&self) -> Result<(), _> {
+        Ok(())
+    }
+}
+
+#[test]
+fn describes_the_enums_of_the_scope() {
+    let [load, save] = store_impl_catalog::ERROR_CATALOG else {
+        panic!("expected two enums");
+    };
+    assert_eq!(load.name, "StoreImplLoadError");
+    assert_eq!(load.function, "load");
+    let [io, parse] = load.variants else {
+        panic!("expected two variants");
+    };
+    assert_eq!(io.name, "IoError");
+    assert_eq!(io.member, Some("IoError"));
+    assert!(io.retryable);
+    assert_eq!(io.severity, "Error");
+    assert_eq!(parse.member, Some("ParseIntError"));
+    assert!(!parse.retryable);
+    assert_eq!(parse.code, StoreImplLoadErrorKind::ParseIntError as u16);
+
+    assert_eq!(save.name, "StoreImplSaveError");
+    let [full] = save.variants else {
+        panic!("expected one variant");
+    };
+    assert_eq!(full.member, None);
+    assert_eq!(full.message, Some("store is full"));
+    assert!(Store.load().is_ok() && Store.save().is_ok());
+}
+
+mod errors {
+    composerr::error_catalog_types!();
+}
+
+#[compose_errors(catalog = "crate::errors", cli)]
+#[errorset(IoError(exit = 74, hint = "check the path"), ParseIntError)]
+fn read() -> Result<u8, _> {
+    Ok("2".parse()?)
+}
+
+#[compose_errors(catalog = "crate::errors")]
+#[errorset(ParseIntError)]
+fn parse() -> Result<u8, _> {
+    Ok("3".parse()?)
+}
+
+#[test]
+fn shared_types_list_scopes_together() {
+    let all: Vec<&errors::ErrorMeta> = read_catalog::ERROR_CATALOG
+        .iter()
+        .chain(parse_catalog::ERROR_CATALOG)
+        .collect();
+    let names: Vec<_> = all.iter().map(|error| error.name).collect();
+    assert_eq!(names, ["ReadError", "ParseError"]);
+    let io = &all[0].variants[0];
+    assert_eq!(io.exit_code, Some(74));
+    assert_eq!(io.hint, Some("check the path"));
+    assert_eq!(all[1].variants[0].exit_code, None);
+    assert_eq!(read().unwrap() + parse().unwrap(), 5);
+}
@@ -0,0 +1,50 @@
+// The `ComposedError` trait shared by enums composed with `composed_trait`, in place of
+// generating its methods on each.
+use composerr::compose_errors;
+use std::{io::Error as IoError, num::ParseIntError};
+
+composerr::composed_error_trait!(boxed);
+
+#[compose_errors(composed_trait, report)]
+#[errorset(IoError, ParseIntError, Empty : "empty input")]
+fn parse(input: &str) -> Result<u8, _> {
+    if input.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    Ok(input.parse()?)
+}
+
+mod elsewhere {
+    use super::*;
+
+    composerr::composed_error_trait!();
+
+    #[compose_errors(composed_trait = "crate::elsewhere")]
+    #[errorset(IoError)]
+    pub fn open() -> Result<(), _> {
+        Err(IoError::other("closed"))?
+    }
+}
+
+#[test]
+fn default_methods_reach_the_member() {
+    let error = parse("x").unwrap_err();
+    assert!(error.get::<ParseIntError>().is_some());
+    assert!(error.get::<IoError>().is_none());
+    assert_eq!(error.chain().count(), 1);
+    assert_eq!(error.as_dyn_error().to_string(), error.to_string());
+    assert_eq!(error.report(), format!("ParseIntError: {error}"));
+
+    let error = parse("").unwrap_err();
+    assert!(error.member().is_none());
+    let boxed = error.into_boxed();
+    assert!(boxed.downcast_ref::<ParseError>().is_some());
+}
+
+#[test]
+fn trait_declared_in_a_module() {
+    use elsewhere::ComposedError as _;
+
+    let error = elsewhere::open().unwrap_err();
+    assert_eq!(error.get::<IoError>().unwrap().to_string(), "closed");
+}
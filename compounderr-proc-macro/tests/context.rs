@@ -0,0 +1,36 @@
+// `errorset(context)`, its variant and extension trait, with or without body analysis.
+use composerr::compose_errors;
+use std::{error::Error, io::Error as IoError, num::ParseIntError};
+
+#[compose_errors]
+#[errorset(IoError, ParseIntError, context)]
+fn read_port(text: &str) -> Result<u16, _> {
+    if text.is_empty() {
+        return Err(IoError::other("empty")).with_ctx(|| format!("reading {text:?}"));
+    }
+    let port = text.trim().parse().ctx("port is not a number")?;
+    Ok(port)
+}
+
+#[test]
+fn context_wraps_the_error() {
+    assert_eq!(read_port(" 80 ").unwrap(), 80);
+
+    let error = read_port("http").unwrap_err();
+    assert_eq!(error.to_string(), "port is not a number");
+    let ReadPortError::Context { source, .. } = &error else {
+        panic!("expected the context variant, got {error:?}");
+    };
+    assert!(matches!(**source, ReadPortError::ParseIntError(_)));
+    assert!(error.source().is_some());
+
+    let error = read_port("").unwrap_err();
+    assert_eq!(error.to_string(), "reading \"\"");
+}
+
+#[test]
+fn trait_is_callable_by_name() {
+    let result: Result<(), IoError> = Err(IoError::other("disk"));
+    let error = ReadPortErrorContext::ctx(result, "saving").unwrap_err();
+    assert!(matches!(error, ReadPortError::Context { .. }));
+}
@@ -0,0 +1,145 @@
+// `default_errorset(..)`, the errorset of each method without one, on impls, traits and modules,
+// and errorsets relative to it.
+use composerr::compose_errors;
+use std::{io::Error as IoError, num::ParseIntError};
+
+#[derive(Debug, thiserror::Error)]
+#[error("database unavailable")]
+pub struct DbError;
+
+struct Service;
+
+#[compose_errors(default_errorset(IoError, ParseIntError))]
+impl Service {
+    fn start(&self, input: &str) -> Result<u8, _> {
+        Ok(input.parse()?)
+    }
+
+    fn stop(&self) -> Result<(), _> {
+        Err(IoError::other("busy"))?
+    }
+
+    #[errorset(DbError)]
+    fn query(&self) -> Result<u8, _> {
+        Err(DbError)?
+    }
+
+    #[errorset(skip)]
+    fn parse(input: &str) -> Result<u8, ParseIntError> {
+        input.parse()
+    }
+
+    fn name(&self) -> &'static str {
+        "service"
+    }
+}
+
+#[test]
+fn methods_without_errorset_take_the_default() {
+    assert!(matches!(
+        Service.start("x"),
+        Err(ServiceImplStartError::ParseIntError(_))
+    ));
+    let error = Service.stop().unwrap_err();
+    assert!(matches!(error, ServiceImplStopError::IoError(_)));
+    // Both enums have the whole default set
+    let _ = ServiceImplStopErrorKind::ParseIntError;
+    let _ = ServiceImplStartErrorKind::IoError;
+}
+
+#[test]
+fn own_errorset_and_skip_are_kept() {
+    assert!(matches!(
+        Service.query(),
+        Err(ServiceImplQueryError::DbError(_))
+    ));
+    let skipped: Result<u8, ParseIntError> = Service::parse("x");
+    assert!(skipped.is_err());
+    assert_eq!(Service.name(), "service");
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("handshake failed")]
+pub struct TlsError;
+
+#[compose_errors(default_errorset(IoError, ParseIntError))]
+impl Service {
+    #[errorset(+TlsError)]
+    fn connect(&self, secure: bool) -> Result<(), _> {
+        if secure {
+            Err(TlsError)?;
+        }
+        Err(IoError::other("refused"))?
+    }
+
+    #[errorset(-ParseIntError)]
+    fn flush(&self) -> Result<(), _> {
+        Err(IoError::other("closed"))?
+    }
+
+    #[errorset(-ParseIntError, +DbError)]
+    fn sync(&self) -> Result<(), _> {
+        Err(DbError)?
+    }
+}
+
+#[test]
+fn relative_errorsets() {
+    assert!(matches!(
+        Service.connect(true),
+        Err(ServiceImplConnectError::TlsError(_))
+    ));
+    assert!(matches!(
+        Service.connect(false),
+        Err(ServiceImplConnectError::IoError(_))
+    ));
+    let _ = ServiceImplConnectErrorKind::ParseIntError;
+
+    // The only variant left, so the match is exhaustive
+    match Service.flush().unwrap_err() {
+        ServiceImplFlushError::IoError(error) => assert_eq!(error.to_string(), "closed"),
+    }
+    match Service.sync().unwrap_err() {
+        ServiceImplSyncError::IoError(_) => panic!("expected the database error"),
+        ServiceImplSyncError::DbError(_) => {}
+    }
+}
+
+#[compose_errors(default_errorset(IoError))]
+trait Store {
+    fn load(&self) -> Result<Vec<u8>, _>;
+
+    fn size(&self) -> Result<usize, _> {
+        Ok(self.load()?.len())
+    }
+}
+
+struct Empty;
+
+impl Store for Empty {
+    fn load(&self) -> Result<Vec<u8>, StoreTraitLoadError> {
+        Err(IoError::other("missing").into())
+    }
+}
+
+#[test]
+fn traits_take_it_too() {
+    assert!(matches!(Empty.size(), Err(StoreTraitSizeError::IoError(_))));
+}
+
+#[compose_errors(default_errorset(ParseIntError))]
+mod api {
+    use super::*;
+
+    pub fn port(input: &str) -> Result<u16, _> {
+        Ok(input.parse()?)
+    }
+}
+
+#[test]
+fn modules_take_it_too() {
+    assert!(matches!(
+        api::port("x"),
+        Err(api::PortError::ParseIntError(_))
+    ));
+}
@@ -0,0 +1,33 @@
+// `backend = "displaydoc"`, Display coming from the doc comments the macro writes.
+#![cfg(feature = "displaydoc")]
+use composerr::compose_errors;
+use std::{error::Error, io::Error as IoError, num::ParseIntError};
+
+#[compose_errors(backend = "displaydoc")]
+#[errorset(IoError, ParseIntError, Missing { key: String } : "missing {key}")]
+fn lookup(key: &str, value: Option<&str>) -> Result<u8, _> {
+    let value = value.ok_or_else(|| LookupError::Missing { key: key.into() })?;
+    if value.is_empty() {
+        Err(IoError::other("empty"))?;
+    }
+    Ok(value.parse()?)
+}
+
+#[test]
+fn members_and_inline_variants_display() {
+    assert_eq!(lookup("port", Some("8")).unwrap(), 8);
+    let error = lookup("port", None).unwrap_err();
+    assert_eq!(error.to_string(), "missing port");
+
+    let error = lookup("port", Some("")).unwrap_err();
+    assert!(matches!(error, LookupError::IoError(_)));
+    // Transparent like the thiserror backend, the member's own source
+    assert_eq!(error.to_string(), "empty");
+    assert!(error.source().is_none());
+
+    let error = lookup("port", Some("x")).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "x".parse::<u8>().unwrap_err().to_string()
+    );
+}
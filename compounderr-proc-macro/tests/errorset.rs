@@ -0,0 +1,328 @@
+// The #[errorset] grammar, its three delimiters and the options it takes, and what the
+// composed enums offer at runtime.
+use composerr::compose_errors;
+use std::{fmt::Error as FmtError, io, io::Error as IoError, num::ParseIntError};
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+#[error("config is missing {0}")]
+pub struct ConfigError(&'static str);
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("database is down")]
+pub struct DbError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SqlError {
+    #[error("no rows")]
+    RowNotFound,
+    #[error("pool closed")]
+    PoolClosed,
+    #[error("syntax error")]
+    Syntax,
+}
+
+composerr::severity_type!();
+
+#[compose_errors]
+#[errorset(IoError, ParseIntError)]
+fn parenthesized(input: &str) -> Result<u8, _> {
+    if input.is_empty() {
+        return Err(IoError::other("empty").into());
+    }
+    Ok(input.parse()?)
+}
+
+#[compose_errors]
+#[errorset{IoError, ParseIntError}]
+fn braced(input: &str) -> Result<u8, _> {
+    Ok(input.parse()?)
+}
+
+#[compose_errors]
+#[errorset[IoError, ParseIntError]]
+fn bracketed(input: &str) -> Result<u8, _> {
+    Ok(input.parse()?)
+}
+
+#[test]
+fn delimiters_compose_the_same_enum() {
+    assert!(matches!(
+        parenthesized(""),
+        Err(ParenthesizedError::IoError(_))
+    ));
+    assert!(matches!(
+        parenthesized("x"),
+        Err(ParenthesizedError::ParseIntError(_))
+    ));
+    assert!(matches!(braced("x"), Err(BracedError::ParseIntError(_))));
+    assert!(matches!(
+        bracketed("x"),
+        Err(BracketedError::ParseIntError(_))
+    ));
+    assert_eq!(bracketed("7").unwrap(), 7);
+}
+
+#[compose_errors]
+trait Store {
+    #[errorset{IoError, ConfigError}]
+    fn read(&self) -> Result<u8, _>;
+
+    fn plain(&self) -> Result<(), String>;
+
+    #[errorset[DbError]]
+    fn write(&self) -> Result<(), _>;
+}
+
+struct Memory;
+
+impl Store for Memory {
+    fn read(&self) -> Result<u8, StoreTraitReadError> {
+        Err(ConfigError("path").into())
+    }
+
+    fn plain(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn write(&self) -> Result<(), StoreTraitWriteError> {
+        Err(DbError.into())
+    }
+}
+
+#[test]
+fn trait_methods_take_any_delimiter() {
+    assert!(matches!(
+        Memory.read(),
+        Err(StoreTraitReadError::ConfigError(ConfigError("path")))
+    ));
+    assert!(Memory.plain().is_ok());
+    assert!(matches!(
+        Memory.write(),
+        Err(StoreTraitWriteError::DbError(DbError))
+    ));
+}
+
+mod named {
+    use super::*;
+
+    #[compose_errors]
+    #[errorset(ConfigError, DbError, name = "SaveFailed", vis = pub(crate), derive(PartialEq))]
+    pub(crate) fn save() -> Result<(), _> {
+        Err(DbError.into())
+    }
+}
+
+#[test]
+fn name_vis_and_derive() {
+    assert_eq!(named::save(), Err(named::SaveFailed::DbError(DbError)));
+}
+
+#[compose_errors]
+#[errorset(IoError, name = "ReadFailed")]
+#[errorset(ParseIntError)]
+fn merged(input: &str) -> Result<u8, _> {
+    Ok(input.parse()?)
+}
+
+#[test]
+fn several_errorsets_merge() {
+    assert!(matches!(merged("x"), Err(ReadFailed::ParseIntError(_))));
+}
+
+#[compose_errors]
+#[errorset(extend, ConfigError)]
+fn extended(fail: bool) -> Result<(), IoError> {
+    if fail {
+        Err(IoError::other("extended"))?;
+    }
+    Err(ConfigError("key").into())
+}
+
+#[test]
+fn extend_keeps_the_existing_error() {
+    assert!(matches!(extended(true), Err(ExtendedError::IoError(_))));
+    assert!(matches!(
+        extended(false),
+        Err(ExtendedError::ConfigError(_))
+    ));
+}
+
+#[compose_errors]
+#[errorset(newtype, IoError)]
+fn wrapped() -> Result<(), _> {
+    Err(IoError::other("wrapped"))?
+}
+
+#[test]
+fn newtype_wraps_a_single_member() {
+    let error = wrapped().unwrap_err();
+    assert_eq!(error.to_string(), "wrapped");
+}
+
+#[compose_errors]
+#[errorset(default Unknown : "unknown error", IoError)]
+fn defaulted() -> Result<(), _> {
+    Err(DefaultedError::default())
+}
+
+#[test]
+fn default_variant() {
+    let error = defaulted().unwrap_err();
+    assert!(matches!(error, DefaultedError::Unknown));
+    assert_eq!(error.to_string(), "unknown error");
+}
+
+#[compose_errors]
+#[errorset(IoError, NotFound { id: u32 } : "missing {id}", Timeout : "timed out")]
+fn inline(id: u32) -> Result<(), _> {
+    match id {
+        0 => Err(InlineError::Timeout),
+        _ => Err(InlineError::NotFound { id }),
+    }
+}
+
+#[test]
+fn inline_variants() {
+    assert_eq!(inline(3).unwrap_err().to_string(), "missing 3");
+    assert_eq!(inline(0).unwrap_err().to_string(), "timed out");
+}
+
+#[compose_errors]
+#[errorset(Read = IoError, Write = IoError)]
+fn copied(read: bool) -> Result<(), _> {
+    if read {
+        Err(IoError::other("read"))?;
+    }
+    Err(CopiedError::from_write(IoError::other("write")))
+}
+
+#[test]
+fn one_type_under_several_names() {
+    assert!(matches!(copied(true), Err(CopiedError::Read(_))));
+    assert!(matches!(copied(false), Err(CopiedError::Write(_))));
+}
+
+#[compose_errors]
+#[errorset(io::NotFound, io::PermissionDenied, IoOther)]
+fn routed_io(kind: io::ErrorKind) -> Result<(), _> {
+    Err(IoError::from(kind))?
+}
+
+#[test]
+fn io_kinds_route_on_the_kind() {
+    assert!(matches!(
+        routed_io(io::ErrorKind::NotFound),
+        Err(RoutedIoError::IoNotFound(_))
+    ));
+    assert!(matches!(
+        routed_io(io::ErrorKind::PermissionDenied),
+        Err(RoutedIoError::IoPermissionDenied(_))
+    ));
+    assert!(matches!(
+        routed_io(io::ErrorKind::Interrupted),
+        Err(RoutedIoError::IoOther(_))
+    ));
+}
+
+#[compose_errors]
+#[errorset(io::Error, ParseIntError)]
+fn plain_io(input: &str) -> Result<u8, _> {
+    if input.is_empty() {
+        Err(IoError::from(io::ErrorKind::NotFound))?;
+    }
+    Ok(input.parse()?)
+}
+
+#[test]
+fn io_error_is_the_plain_member() {
+    assert!(matches!(plain_io(""), Err(PlainIoError::IoError(_))));
+    assert!(matches!(plain_io("x"), Err(PlainIoError::ParseIntError(_))));
+}
+
+#[compose_errors]
+#[errorset(map(SqlError => {
+    RowNotFound => NotFound,
+    PoolClosed => Unavailable(retryable),
+    _ => Sql,
+}))]
+fn query(error: SqlError) -> Result<(), _> {
+    Err(error)?
+}
+
+#[test]
+fn map_routes_on_patterns() {
+    assert!(matches!(
+        query(SqlError::RowNotFound),
+        Err(QueryError::NotFound(_))
+    ));
+    assert!(matches!(
+        query(SqlError::PoolClosed),
+        Err(QueryError::Unavailable(_))
+    ));
+    assert!(matches!(query(SqlError::Syntax), Err(QueryError::Sql(_))));
+}
+
+#[compose_errors(severity)]
+#[errorset(IoError(retryable), ConfigError(fatal), FmtError)]
+fn classified(which: u8) -> Result<(), _> {
+    match which {
+        0 => Err(IoError::other("io").into()),
+        1 => Err(ConfigError("key").into()),
+        _ => Err(FmtError.into()),
+    }
+}
+
+#[test]
+fn kinds_and_accessors() {
+    let error = classified(0).unwrap_err();
+    assert_eq!(error.kind(), ClassifiedErrorKind::IoError);
+    assert!(error.is_retryable());
+    assert!(error.get::<IoError>().is_some());
+    assert!(error.get::<ConfigError>().is_none());
+    assert_eq!(error.chain().count(), 1);
+
+    let error = classified(1).unwrap_err();
+    assert!(!error.is_retryable());
+    assert_eq!(error.severity(), Severity::Fatal);
+    let error = ConfigError::try_from(error).unwrap();
+    assert_eq!(error, ConfigError("key"));
+
+    let error = classified(2).unwrap_err();
+    let error = IoError::try_from(error).unwrap_err();
+    assert_eq!(error.kind(), ClassifiedErrorKind::FmtError);
+}
+
+#[compose_errors(boxed)]
+#[errorset(IoError, ConfigError)]
+fn boxed() -> Result<(), _> {
+    Err(ConfigError("user").into())
+}
+
+#[test]
+fn into_boxed_with_the_option() {
+    let error: Box<dyn std::error::Error + Send + Sync> = boxed().unwrap_err().into_boxed();
+    assert_eq!(error.to_string(), "config is missing user");
+    assert!(error.downcast_ref::<BoxedError>().is_some());
+}
+
+#[compose_errors(vis = pub(crate), derive(Clone))]
+mod api {
+    use super::*;
+
+    #[errorset(DbError)]
+    pub fn load() -> Result<u8, _> {
+        Err(DbError.into())
+    }
+
+    #[compose_errors(skip)]
+    pub fn raw() -> Result<u8, DbError> {
+        Err(DbError)
+    }
+}
+
+#[test]
+fn module_scope() {
+    let error = api::load().unwrap_err();
+    assert!(matches!(error.clone(), api::LoadError::DbError(DbError)));
+    assert_eq!(api::raw(), Err(DbError));
+}
@@ -0,0 +1,57 @@
+// `export_errorset!` in a module of base errors, taken whole by an errorset entry `module::*`.
+use composerr::compose_errors;
+use std::num::ParseIntError;
+
+mod base {
+    #[derive(Debug, thiserror::Error)]
+    #[error("based error")]
+    pub struct BasedError;
+
+    #[derive(Debug, thiserror::Error)]
+    #[error(transparent)]
+    pub struct IoError(#[from] std::io::Error);
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("config is missing {0}")]
+    pub struct ConfigError(pub &'static str);
+
+    composerr::export_errorset!(BasedError, IoError, ConfigError);
+}
+
+#[compose_errors]
+#[errorset(crate::base::*, ParseIntError)]
+fn load(input: &str) -> Result<u8, _> {
+    match input {
+        "" => Err(base::ConfigError("value"))?,
+        "?" => Err(base::BasedError)?,
+        _ => Ok(input.parse()?),
+    }
+}
+
+#[test]
+fn module_members_are_spliced_in() {
+    assert_eq!(load("4").unwrap(), 4);
+    assert!(matches!(load(""), Err(LoadError::ConfigError(_))));
+    assert!(matches!(load("?"), Err(LoadError::BasedError(_))));
+    assert!(matches!(load("x"), Err(LoadError::ParseIntError(_))));
+    let io: LoadError = base::IoError::from(std::io::Error::other("disk")).into();
+    assert_eq!(io.kind(), LoadErrorKind::IoError);
+}
+
+struct Loader;
+
+#[compose_errors]
+impl Loader {
+    #[errorset(base::*)]
+    fn check(&self) -> Result<(), _> {
+        Err(base::BasedError)?
+    }
+}
+
+#[test]
+fn relative_module_path_in_a_method() {
+    assert!(matches!(
+        Loader.check(),
+        Err(LoaderImplCheckError::BasedError(_))
+    ));
+}
@@ -0,0 +1,49 @@
+// `ffi`, the C ABI code of each error, with the default repr and with `repr`.
+use composerr::compose_errors;
+use std::{io::Error as IoError, mem::size_of, num::ParseIntError};
+
+#[compose_errors(ffi)]
+#[errorset(IoError, ParseIntError)]
+fn load(input: &str) -> Result<u8, _> {
+    if input.is_empty() {
+        Err(IoError::other("empty"))?;
+    }
+    Ok(input.parse()?)
+}
+
+#[test]
+fn codes_follow_the_kinds() {
+    assert_eq!(load("").unwrap_err().to_ffi(), LoadErrorFfiCode::IoError);
+    let code = load("x").unwrap_err().to_ffi();
+    assert_eq!(code, LoadErrorFfiCode::ParseIntError);
+    assert_eq!(code as i32, LoadErrorKind::ParseIntError as i32);
+    assert_eq!(size_of::<LoadErrorFfiCode>(), size_of::<std::ffi::c_int>());
+}
+
+#[compose_errors(ffi, repr = "i32")]
+#[errorset(IoError(discriminant = 1), ParseIntError, Timeout(discriminant = 10) : "timed out")]
+fn fetch(input: &str) -> Result<u8, _> {
+    match input {
+        "" => Err(IoError::other("empty"))?,
+        "late" => Err(FetchError::Timeout)?,
+        _ => Ok(input.parse()?),
+    }
+}
+
+// How a C caller sees it, 0 being left free for success
+extern "C" fn config_fetch(input: u8) -> i32 {
+    let input = ["4", "", "x", "late"][usize::from(input)];
+    match fetch(input) {
+        Ok(_) => 0,
+        Err(e) => e.to_ffi() as i32,
+    }
+}
+
+#[test]
+fn repr_and_discriminants_are_kept() {
+    assert_eq!(size_of::<FetchErrorFfiCode>(), size_of::<i32>());
+    assert_eq!(config_fetch(0), 0);
+    assert_eq!(config_fetch(1), 1);
+    assert_eq!(config_fetch(2), 2);
+    assert_eq!(config_fetch(3), 10);
+}
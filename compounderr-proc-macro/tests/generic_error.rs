@@ -0,0 +1,127 @@
+// Error type parameters replaced by the composed enum, in where clauses and default bodies.
+use composerr::compose_errors;
+use std::{io::Error as IoError, num::ParseIntError};
+
+#[derive(Debug, thiserror::Error)]
+#[error("missing key")]
+pub struct Missing;
+
+#[compose_errors]
+#[errorset(IoError, ParseIntError)]
+fn bounded<E: From<IoError>>(input: &str) -> Result<u8, E> {
+    if input.is_empty() {
+        return Err(E::from(IoError::other("empty")));
+    }
+    Ok(input.parse()?)
+}
+
+#[compose_errors]
+#[errorset(IoError, ParseIntError)]
+fn in_where_clause<E, T>(input: T) -> Result<u8, E>
+where
+    E: From<IoError>,
+    T: AsRef<str>,
+{
+    Ok(input.as_ref().parse()?)
+}
+
+#[compose_errors]
+#[errorset(IoError, Missing)]
+fn mentioned<E, M>(missing: M) -> Result<(), E>
+where
+    E: From<IoError>,
+    M: Into<E>,
+{
+    Err(missing.into())
+}
+
+#[test]
+fn parameter_becomes_the_enum() {
+    assert!(matches!(bounded(""), Err(BoundedError::IoError(_))));
+    assert!(matches!(bounded("x"), Err(BoundedError::ParseIntError(_))));
+    assert!(matches!(
+        in_where_clause("x"),
+        Err(InWhereClauseError::ParseIntError(_))
+    ));
+    assert_eq!(in_where_clause(String::from("9")).unwrap(), 9);
+    assert!(matches!(
+        mentioned(Missing),
+        Err(MentionedError::Missing(_))
+    ));
+}
+
+#[compose_errors]
+trait Config {
+    fn raw(&self, key: &str) -> Option<String>;
+
+    #[errorset(Missing, ParseIntError)]
+    fn number(&self, key: &str) -> Result<u32, _> {
+        let raw = self.raw(key).ok_or(Missing)?;
+        Ok(raw.parse()?)
+    }
+
+    #[errorset(Missing, ParseIntError)]
+    fn number_or<E: From<Missing>>(&self, key: &str) -> Result<u32, E>
+    where
+        Self: Sized,
+    {
+        match self.raw(key) {
+            Some(raw) => Ok(raw.parse()?),
+            None => Err(Missing.into()),
+        }
+    }
+}
+
+struct Fixed;
+
+impl Config for Fixed {
+    fn raw(&self, key: &str) -> Option<String> {
+        (key == "port").then(|| "80x".to_string())
+    }
+}
+
+#[test]
+fn default_bodies_convert_into_the_enum() {
+    assert!(matches!(
+        Fixed.number("host"),
+        Err(ConfigTraitNumberError::Missing(_))
+    ));
+    assert!(matches!(
+        Fixed.number("port"),
+        Err(ConfigTraitNumberError::ParseIntError(_))
+    ));
+    assert!(matches!(
+        Fixed.number_or("host"),
+        Err(ConfigTraitNumberOrError::Missing(_))
+    ));
+}
+
+#[cfg(feature = "body-analysis")]
+#[compose_errors]
+trait Lookup {
+    fn has(&self, key: &str) -> bool;
+
+    #[errorset(Missing, IoError)]
+    fn check(&self, key: &str) -> Result<(), _> {
+        if self.has(key) {
+            Ok(())
+        } else {
+            Err(Missing)
+        }
+    }
+}
+
+#[cfg(feature = "body-analysis")]
+#[test]
+fn default_bodies_return_members() {
+    struct Empty;
+    impl Lookup for Empty {
+        fn has(&self, _: &str) -> bool {
+            false
+        }
+    }
+    assert!(matches!(
+        Empty.check("key"),
+        Err(LookupTraitCheckError::Missing(_))
+    ));
+}
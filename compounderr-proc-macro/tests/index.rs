@@ -0,0 +1,48 @@
+// `index`, a module per scope re-exporting its enums, kinds and result aliases.
+use std::{io::Error as IoError, num::ParseIntError};
+
+mod store {
+    use super::*;
+    use composerr::compose_errors;
+
+    pub struct Store;
+
+    #[compose_errors(index, result_alias, vis = pub)]
+    impl Store {
+        #[errorset(IoError)]
+        pub fn load(&self) -> Result<u8, _> {
+            Err(IoError::other("disk"))?
+        }
+
+        #[errorset(ParseIntError)]
+        pub fn parse(&self, input: &str) -> Result<u8, _> {
+            Ok(input.parse()?)
+        }
+    }
+
+    #[compose_errors(index, vis = pub)]
+    #[errorset(IoError)]
+    pub fn open() -> Result<(), _> {
+        Ok(())
+    }
+}
+
+#[test]
+fn scope_errors_glob_import() {
+    use store::store_impl_errors::*;
+
+    let error: StoreImplLoadError = store::Store.load().unwrap_err();
+    assert_eq!(error.kind(), StoreImplLoadErrorKind::IoError);
+    let parsed: StoreImplParseResult<u8> = store::Store.parse("x");
+    assert!(matches!(parsed, Err(StoreImplParseError::ParseIntError(_))));
+}
+
+#[test]
+fn bare_functions_are_their_own_scope() {
+    use store::open_errors::*;
+
+    let opened: Result<(), OpenError> = store::open();
+    assert!(opened.is_ok());
+    let kind: fn(&OpenError) -> OpenErrorKind = OpenError::kind;
+    assert_eq!(kind(&IoError::other("disk").into()), OpenErrorKind::IoError);
+}
@@ -0,0 +1,38 @@
+// `json`, errors rendered as a `serde_json::Value` with their kind, code and sources.
+#![cfg(feature = "serde-json")]
+use composerr::compose_errors;
+use serde_json::json;
+use std::{io::Error as IoError, num::ParseIntError};
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid config")]
+pub struct ConfigError(#[source] IoError);
+
+#[compose_errors(json)]
+#[errorset(ParseIntError, ConfigError, Missing { key: String } : "missing {key}")]
+fn load(input: &str) -> Result<u8, _> {
+    match input {
+        "" => Err(LoadError::Missing { key: "port".into() })?,
+        "?" => Err(ConfigError(IoError::other("permission denied")))?,
+        _ => Ok(input.parse()?),
+    }
+}
+
+#[test]
+fn kind_code_message_and_sources() {
+    let error = load("?").unwrap_err();
+    assert_eq!(
+        error.to_json(),
+        json!({
+            "code": LoadErrorKind::ConfigError as u16,
+            "kind": "ConfigError",
+            "message": "invalid config",
+            "source_chain": ["permission denied"],
+        })
+    );
+
+    let value = load("").unwrap_err().to_json();
+    assert_eq!(value["kind"], "Missing");
+    assert_eq!(value["message"], "missing port");
+    assert_eq!(value["source_chain"], json!([]));
+}
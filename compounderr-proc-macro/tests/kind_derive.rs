@@ -0,0 +1,21 @@
+// `kind_derive(..)`, derives only the fieldless kind enum takes, as schemas for API docs.
+use composerr::compose_errors;
+use std::io::Error as IoError;
+
+#[compose_errors(kind_derive(serde::Serialize, schemars::JsonSchema))]
+#[errorset(IoError, NotFound { id: u32 } : "missing {id}")]
+fn fetch(id: u32) -> Result<u32, _> {
+    match id {
+        0 => Err(FetchError::NotFound { id }),
+        _ => Ok(id),
+    }
+}
+
+#[test]
+fn schema_lists_the_kinds() {
+    let schema = serde_json::to_value(schemars::schema_for!(FetchErrorKind)).unwrap();
+    assert_eq!(schema["enum"], serde_json::json!(["IoError", "NotFound"]));
+
+    let kind = fetch(0).unwrap_err().kind();
+    assert_eq!(serde_json::to_value(kind).unwrap(), "NotFound");
+}
@@ -0,0 +1,51 @@
+// `errorset(map_err)`: each `?` of the body converts through the composed enum explicitly,
+// where inference alone couldn't pick the conversion.
+#![cfg(feature = "body-analysis")]
+
+use composerr::compose_errors;
+use std::{
+    future::Future,
+    io::Error as IoError,
+    num::ParseIntError,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut context = Context::from_waker(Waker::noop());
+    match pin!(future).poll(&mut context) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("the future waited"),
+    }
+}
+
+#[compose_errors]
+#[errorset(map_err, IoError, ParseIntError)]
+fn sum(s: &str) -> Result<u32, _> {
+    // Without map_err, the error type collected into is ambiguous
+    let parsed: Vec<u32> = s.split(',').map(str::parse).collect::<Result<_, _>>()?;
+    Ok(parsed.iter().sum())
+}
+
+#[test]
+fn collected_results_convert() {
+    assert_eq!(sum("1,2,3").unwrap(), 6);
+    assert!(matches!(sum("1,x"), Err(SumError::ParseIntError(_))));
+}
+
+#[compose_errors]
+#[errorset(map_err, IoError, ParseIntError)]
+fn nested(s: &str) -> Result<u32, _> {
+    // `?` in a closure or an async block returns from that, with its own error type
+    let parse = |s: &str| -> Result<u32, ParseIntError> { Ok(s.parse::<u32>()? * 2) };
+    let later = async { Ok::<u32, ParseIntError>(s.parse::<u32>()? + 1) };
+    let doubled = parse(s)?;
+    let added = block_on(later)?;
+    Ok(doubled + added)
+}
+
+#[test]
+fn closures_and_async_blocks_are_left_alone() {
+    assert_eq!(nested("2").unwrap(), 7);
+    assert!(matches!(nested("x"), Err(NestedError::ParseIntError(_))));
+}
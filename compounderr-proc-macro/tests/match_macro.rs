@@ -0,0 +1,40 @@
+// `match_macro`, matching the variants by their snake case names.
+use composerr::compose_errors;
+use std::{io::Error as IoError, num::ParseIntError};
+
+#[compose_errors(match_macro)]
+#[errorset(IoError, ParseIntError, Timeout { secs: u64 } : "timed out after {secs}s")]
+fn fetch(input: &str) -> Result<u8, _> {
+    match input {
+        "" => Err(IoError::other("empty"))?,
+        "late" => Err(FetchError::Timeout { secs: 30 })?,
+        _ => Ok(input.parse()?),
+    }
+}
+
+fn status(error: FetchError) -> String {
+    match_fetch_error!(error,
+        io_error(e) => format!("503 {e}"),
+        parse_int_error => "400".to_string(),
+        timeout { secs } => format!("504 after {secs}s"),
+    )
+}
+
+#[test]
+fn arms_bind_patterns_and_fields() {
+    assert_eq!(status(fetch("").unwrap_err()), "503 empty");
+    assert_eq!(status(fetch("x").unwrap_err()), "400");
+    assert_eq!(status(fetch("late").unwrap_err()), "504 after 30s");
+}
+
+#[test]
+fn wildcard_closes_the_match() {
+    let retryable = |error: &FetchError| {
+        match_fetch_error!(error,
+            timeout { .. } => true,
+            _ => false,
+        )
+    };
+    assert!(retryable(&fetch("late").unwrap_err()));
+    assert!(!retryable(&fetch("x").unwrap_err()));
+}
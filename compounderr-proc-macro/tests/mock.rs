@@ -0,0 +1,80 @@
+// The `mock` test double of a trait: programmed methods, futures and iterators, and the
+// methods that can't be programmed.
+use composerr::compose_errors;
+use std::{
+    future::Future,
+    io::Error as IoError,
+    num::ParseIntError,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut context = Context::from_waker(Waker::noop());
+    match pin!(future).poll(&mut context) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("the future waited"),
+    }
+}
+
+#[compose_errors(mock)]
+pub trait Store {
+    #[errorset(IoError, ParseIntError)]
+    fn load(&self, key: &str) -> Result<u32, _>;
+
+    fn fetch(&self) -> impl Future<Output = Option<u32>>;
+
+    fn keys(&self) -> impl Iterator<Item = String>;
+
+    fn name(&self) -> &str;
+
+    fn prefixed<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a str>;
+
+    fn describe(&self) -> String {
+        format!("store {}", self.name())
+    }
+}
+
+#[test]
+fn programmed_methods_return_the_closure_value() {
+    let mut calls = 0;
+    let store = MockStore::new()
+        .on_load(move || {
+            calls += 1;
+            match calls {
+                1 => Ok(7),
+                _ => Err(IoError::other("gone").into()),
+            }
+        })
+        .on_fetch(|| Some(3))
+        .on_keys(|| vec!["a".to_string(), "b".to_string()]);
+    assert_eq!(store.load("a").unwrap(), 7);
+    assert!(matches!(
+        store.load("a"),
+        Err(StoreTraitLoadError::IoError(_))
+    ));
+    assert_eq!(block_on(store.fetch()), Some(3));
+    assert_eq!(store.keys().collect::<Vec<_>>(), ["a", "b"]);
+}
+
+#[test]
+#[should_panic(expected = "MockStore::load called without being programmed")]
+fn unprogrammed_methods_panic() {
+    let _ = MockStore::new().load("a");
+}
+
+#[test]
+#[should_panic(
+    expected = "MockStore::name can't be programmed, its output borrows or mentions `Self`"
+)]
+fn borrowing_methods_panic_with_the_reason() {
+    let _ = MockStore::new().describe();
+}
+
+#[test]
+#[should_panic(expected = "MockStore::prefixed can't be programmed")]
+fn borrowing_iterators_panic_when_iterated() {
+    let store = MockStore::new();
+    let mut prefixed = store.prefixed("a");
+    prefixed.next();
+}
@@ -0,0 +1,91 @@
+// `#[compose_errors]` on an inline module, recursing into its items and nested modules.
+use composerr::compose_errors;
+use std::{io::Error as IoError, num::ParseIntError};
+
+#[compose_errors(derive(Clone))]
+mod api {
+    use super::*;
+    use std::sync::Arc;
+
+    // Members must be Clone, as the module's derive reaches every item
+    pub type SharedIoError = Arc<IoError>;
+
+    #[errorset(ParseIntError)]
+    pub fn port(input: &str) -> Result<u16, _> {
+        Ok(input.parse()?)
+    }
+
+    pub struct Store;
+
+    impl Store {
+        #[errorset(SharedIoError)]
+        pub fn load(&self) -> Result<u8, _> {
+            Err(Arc::new(IoError::other("disk")))?
+        }
+    }
+
+    pub trait Source {
+        #[errorset(ParseIntError)]
+        fn read(&self) -> Result<u8, _>;
+    }
+
+    impl Source for Store {
+        fn read(&self) -> Result<u8, SourceTraitReadError> {
+            Ok("x".parse()?)
+        }
+    }
+
+    pub mod admin {
+        use super::*;
+
+        #[errorset(ParseIntError)]
+        pub fn reset(input: &str) -> Result<u8, _> {
+            Ok(input.parse()?)
+        }
+
+        // Own options replace the module's
+        #[compose_errors(repr = "u8")]
+        #[errorset(SharedIoError(discriminant = 5))]
+        pub fn wipe() -> Result<(), _> {
+            Err(Arc::new(IoError::other("read only")))?
+        }
+    }
+
+    #[compose_errors(skip)]
+    pub mod raw {
+        pub fn port(input: &str) -> Result<u16, std::num::ParseIntError> {
+            input.parse()
+        }
+    }
+}
+
+#[test]
+fn items_of_the_module_are_composed() {
+    let error = api::port("x").unwrap_err();
+    assert!(matches!(error.clone(), api::PortError::ParseIntError(_)));
+    assert!(matches!(
+        api::Store.load(),
+        Err(api::StoreImplLoadError::SharedIoError(_))
+    ));
+    use api::Source;
+    assert!(matches!(
+        api::Store.read(),
+        Err(api::SourceTraitReadError::ParseIntError(_))
+    ));
+}
+
+#[test]
+fn nested_modules_recurse() {
+    let error = api::admin::reset("x").unwrap_err();
+    assert!(matches!(
+        error.clone(),
+        api::admin::ResetError::ParseIntError(_)
+    ));
+    assert_eq!(api::admin::wipe().unwrap_err().kind() as u8, 5);
+}
+
+#[test]
+fn skipped_module_is_left_alone() {
+    let parsed: Result<u16, ParseIntError> = api::raw::port("x");
+    assert!(parsed.is_err());
+}
@@ -0,0 +1,20 @@
+// Checks the `tests/no_std` crate, composing enums with the plain backend under
+// `#![no_std]` and without alloc. A library is only checked, so no panic handler is needed.
+use std::process::Command;
+
+#[test]
+fn composes_without_std() {
+    let manifest = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/no_std/Cargo.toml");
+    let target_dir = concat!(env!("CARGO_TARGET_TMPDIR"), "/no_std");
+    // Its dependencies are composerr's own, already fetched to build this test
+    let output = Command::new(env!("CARGO"))
+        .args(["check", "--offline", "--quiet", "--manifest-path", manifest])
+        .args(["--target-dir", target_dir])
+        .output()
+        .expect("cargo runs");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
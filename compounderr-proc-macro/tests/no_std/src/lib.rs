@@ -0,0 +1,74 @@
+#![no_std]
+#![deny(warnings)]
+
+use composerr::compose_errors;
+use core::{fmt::Error as FmtError, num::ParseIntError, str::Utf8Error};
+
+#[derive(Debug)]
+pub struct SensorError;
+
+impl core::fmt::Display for SensorError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("sensor fault")
+    }
+}
+
+impl core::error::Error for SensorError {}
+
+composerr::severity_type!();
+
+#[compose_errors(backend = "plain", severity, repr = "u8", variant_names, result_alias, kind_eq)]
+#[errorset(ParseIntError(retryable), SensorError(fatal), Utf8Error, Timeout : "timed out")]
+pub fn read(raw: &[u8]) -> Result<u8, _> {
+    let text = core::str::from_utf8(raw)?;
+    if text.is_empty() {
+        return Err(ReadError::Timeout);
+    }
+    if text == "!" {
+        return Err(SensorError.into());
+    }
+    Ok(text.parse()?)
+}
+
+#[compose_errors(backend = "plain", widen, visitor, match_macro, constructors, bail_macros)]
+#[errorset(ParseIntError, Utf8Error, FmtError)]
+pub fn parse(raw: &[u8]) -> Result<u8, _> {
+    parse_error_ensure!(!raw.is_empty(), FmtError);
+    let text = core::str::from_utf8(raw)?;
+    Ok(text.parse()?)
+}
+
+#[compose_errors(backend = "plain")]
+impl SensorError {
+    #[errorset(ParseIntError)]
+    pub fn calibrate(&self, raw: &str) -> Result<u8, _> {
+        Ok(raw.parse()?)
+    }
+}
+
+// The generated helpers, called without std
+pub fn classify(raw: &[u8]) -> Option<(u8, bool, Severity, &'static str)> {
+    let error = read(raw).err()?;
+    let retryable = error.is_retryable();
+    let severity = error.severity();
+    let _ = error.get::<ParseIntError>();
+    let _ = error.chain().count();
+    let _ = error.as_dyn_error();
+    let name = error.variant_name();
+    let error = ParseIntError::try_from(error).err()?;
+    Some((error.kind() as u8, retryable, severity, name))
+}
+
+composerr::composed_error_trait!();
+
+#[compose_errors(backend = "plain", composed_trait)]
+#[errorset(ParseIntError, SensorError)]
+pub fn measure(raw: &str) -> Result<u8, _> {
+    Ok(raw.parse()?)
+}
+
+pub fn measured_cause(raw: &str) -> Option<usize> {
+    let error = measure(raw).err()?;
+    let _ = error.get::<SensorError>();
+    Some(error.chain().count())
+}
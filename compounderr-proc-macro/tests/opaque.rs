@@ -0,0 +1,54 @@
+// `opaque(..)`, with its predicate holding and not, the struct and the enum reporting alike.
+use composerr::compose_errors;
+use std::{error::Error, io::Error as IoError, num::ParseIntError};
+
+// `all()` always holds, the struct replaces the enum
+#[compose_errors(opaque(all()))]
+#[errorset(IoError, ParseIntError(retryable))]
+fn small(input: &str) -> Result<u8, _> {
+    if input == "io" {
+        Err(IoError::other("disk"))?;
+    }
+    Ok(input.parse()?)
+}
+
+// `any()` never holds, the full enum is kept
+#[compose_errors(opaque(any()))]
+#[errorset(IoError, ParseIntError(retryable))]
+fn full(input: &str) -> Result<u8, _> {
+    if input == "io" {
+        Err(IoError::other("disk"))?;
+    }
+    Ok(input.parse()?)
+}
+
+#[test]
+fn struct_keeps_the_kind() {
+    assert_eq!(small("7").unwrap(), 7);
+    let error = small("x").unwrap_err();
+    assert_eq!(error.kind(), SmallErrorKind::ParseIntError);
+    assert!(error.is_retryable());
+    assert_eq!(error.to_string(), format!("error code {}", error.code()));
+    assert!(error.source().is_none());
+    assert!(error.get::<ParseIntError>().is_none());
+    assert_eq!(error.chain().count(), 1);
+
+    let error = small("io").unwrap_err();
+    assert_eq!(error.kind(), SmallErrorKind::IoError);
+    assert!(!error.is_retryable());
+    assert_ne!(error.code(), small("x").unwrap_err().code());
+}
+
+#[test]
+fn enum_reports_the_same_codes() {
+    let error = full("x").unwrap_err();
+    assert!(matches!(error, FullError::ParseIntError(_)));
+    assert_eq!(error.kind(), FullErrorKind::ParseIntError);
+    assert!(error.is_retryable());
+    assert_eq!(error.code(), small("x").unwrap_err().code());
+    assert_eq!(
+        full("io").unwrap_err().code(),
+        small("io").unwrap_err().code()
+    );
+    assert!(error.get::<ParseIntError>().is_some());
+}
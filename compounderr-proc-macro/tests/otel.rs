@@ -0,0 +1,38 @@
+// `otel`, the kind, code and retryability of errors as OpenTelemetry attributes.
+#![cfg(feature = "otel")]
+use composerr::compose_errors;
+use opentelemetry::{Key, Value};
+use std::{io::Error as IoError, num::ParseIntError};
+
+#[compose_errors(otel)]
+#[errorset(IoError(retryable), ParseIntError)]
+fn load(input: &str) -> Result<u8, _> {
+    if input.is_empty() {
+        Err(IoError::other("busy"))?;
+    }
+    Ok(input.parse()?)
+}
+
+#[test]
+fn attributes_of_the_error() {
+    let attributes = load("").unwrap_err().otel_attributes();
+    let pairs: Vec<(Key, Value)> = attributes
+        .into_iter()
+        .map(|attribute| (attribute.key, attribute.value))
+        .collect();
+    assert_eq!(
+        pairs,
+        [
+            (Key::new("error.type"), Value::from("IoError")),
+            (
+                Key::new("error.code"),
+                Value::I64(LoadErrorKind::IoError as i64)
+            ),
+            (Key::new("error.retryable"), Value::Bool(true)),
+        ]
+    );
+
+    let [kind, _, retryable] = load("x").unwrap_err().otel_attributes();
+    assert_eq!(kind.value.as_str(), "ParseIntError");
+    assert_eq!(retryable.value, Value::Bool(false));
+}
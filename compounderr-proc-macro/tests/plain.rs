@@ -0,0 +1,56 @@
+// `backend = "plain"`, Display and Error written by the macro without an error crate.
+use composerr::compose_errors;
+use std::{error::Error, io::Error as IoError, num::ParseIntError};
+
+#[compose_errors(backend = "plain")]
+#[errorset(IoError, ParseIntError, Missing { key: String } : "missing {key}", Wrapped { source: IoError } : "while saving")]
+fn lookup(key: &str, value: Option<&str>) -> Result<u8, _> {
+    let value = value.ok_or_else(|| LookupError::Missing { key: key.into() })?;
+    match value {
+        "" => Err(IoError::other("empty"))?,
+        "save" => Err(LookupError::Wrapped {
+            source: IoError::other("disk full"),
+        })?,
+        _ => Ok(value.parse()?),
+    }
+}
+
+#[test]
+fn members_and_inline_variants_display() {
+    assert_eq!(lookup("port", Some("8")).unwrap(), 8);
+    assert_eq!(
+        lookup("port", None).unwrap_err().to_string(),
+        "missing port"
+    );
+
+    let error = lookup("port", Some("")).unwrap_err();
+    assert!(matches!(error, LookupError::IoError(_)));
+    assert_eq!(error.to_string(), "empty");
+
+    let error = lookup("port", Some("x")).unwrap_err();
+    assert!(matches!(error, LookupError::ParseIntError(_)));
+    assert_eq!(
+        error.to_string(),
+        "x".parse::<u8>().unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn source_field_of_inline_variants() {
+    let error = lookup("port", Some("save")).unwrap_err();
+    assert_eq!(error.to_string(), "while saving");
+    assert_eq!(error.source().unwrap().to_string(), "disk full");
+}
+
+#[compose_errors(backend = "plain", track_caller)]
+#[errorset(ParseIntError)]
+fn located(input: &str) -> Result<u8, _> {
+    Ok(input.parse()?)
+}
+
+#[test]
+fn track_caller_appends_the_location() {
+    let error = located("x").unwrap_err();
+    assert!(error.to_string().contains(file!()), "{error}");
+    assert!(error.source().is_some());
+}
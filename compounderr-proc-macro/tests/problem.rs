@@ -0,0 +1,71 @@
+// `problem`, RFC 7807 problem details from the members' annotations and defaults.
+#![cfg(feature = "problem-details")]
+use composerr::compose_errors;
+use std::io::Error as IoError;
+
+composerr::problem_details_type!(#[derive(serde::Serialize)]);
+
+#[derive(Debug, thiserror::Error)]
+#[error("user {0} not found")]
+pub struct NotFoundError(u64);
+
+#[compose_errors(problem)]
+#[errorset(
+    NotFoundError(status = 404, problem_type = "https://example.com/probs/not-found"),
+    RateLimited(status = 429, title = "Slow down") : "too many requests",
+    IoError,
+)]
+fn get_user(id: u64) -> Result<String, _> {
+    match id {
+        0 => Err(NotFoundError(id))?,
+        1 => Err(GetUserError::RateLimited)?,
+        2 => Err(IoError::other("disk"))?,
+        _ => Ok(format!("user {id}")),
+    }
+}
+
+#[test]
+fn annotations_fill_the_problem() {
+    let problem = get_user(0).unwrap_err().to_problem();
+    assert_eq!(problem.r#type, "https://example.com/probs/not-found");
+    assert_eq!(problem.status, 404);
+    assert_eq!(problem.detail, "user 0 not found");
+
+    let problem = get_user(1).unwrap_err().to_problem();
+    assert_eq!(
+        serde_json::to_value(&problem).unwrap(),
+        serde_json::json!({
+            "type": "about:blank",
+            "title": "Slow down",
+            "status": 429,
+            "detail": "too many requests",
+            "instance": null,
+        })
+    );
+}
+
+#[test]
+fn unannotated_members_take_the_defaults() {
+    let problem = get_user(2).unwrap_err().to_problem();
+    assert_eq!(problem.r#type, "about:blank");
+    assert_eq!(problem.status, 500);
+    assert_eq!(problem.title, "Io error");
+    assert_eq!(problem.detail, "disk");
+}
+
+mod api {
+    composerr::problem_details_type!();
+}
+
+#[compose_errors(problem = "crate::api")]
+#[errorset(NotFoundError(status = 404))]
+fn get_post(id: u64) -> Result<(), _> {
+    Err(NotFoundError(id))?
+}
+
+#[test]
+fn type_declared_in_a_module() {
+    let problem: api::ProblemDetails = get_post(3).unwrap_err().to_problem();
+    assert_eq!(problem.status, 404);
+    assert_eq!(problem.title, "Not found error");
+}
@@ -0,0 +1,52 @@
+// `report_hook`, called as members enter the composed enum and not again between siblings.
+use composerr::compose_errors;
+use std::{cell::RefCell, error::Error, io::Error as IoError, num::ParseIntError};
+
+thread_local! {
+    static REPORTED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+fn capture(error: &dyn Error) {
+    REPORTED.with(|reported| reported.borrow_mut().push(error.to_string()));
+}
+
+fn reported() -> Vec<String> {
+    REPORTED.with(|reported| reported.take())
+}
+
+struct Jobs;
+
+#[compose_errors(report_hook = "crate::capture")]
+impl Jobs {
+    #[errorset(IoError, ParseIntError)]
+    fn fetch(&self, input: &str) -> Result<u8, _> {
+        if input.is_empty() {
+            Err(IoError::other("disk"))?;
+        }
+        Ok(input.parse()?)
+    }
+
+    #[errorset(IoError, ParseIntError, Cancelled : "cancelled")]
+    fn run(&self, input: &str) -> Result<u8, _> {
+        if input == "stop" {
+            Err(JobsImplRunError::Cancelled)?;
+        }
+        Ok(self.fetch(input)?)
+    }
+}
+
+#[test]
+fn members_are_reported_where_they_enter() {
+    assert!(Jobs.fetch("").is_err());
+    assert_eq!(reported(), ["disk"]);
+    assert_eq!(Jobs.fetch("4").unwrap(), 4);
+    assert!(reported().is_empty());
+}
+
+#[test]
+fn siblings_and_inline_variants_are_not_reported_again() {
+    assert!(Jobs.run("x").is_err());
+    assert_eq!(reported(), ["x".parse::<u8>().unwrap_err().to_string()]);
+    assert!(Jobs.run("stop").is_err());
+    assert!(reported().is_empty());
+}
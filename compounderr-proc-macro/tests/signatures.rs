@@ -0,0 +1,225 @@
+// Signatures the macro rewrites: qualifiers, generics, methods and `Result<T, _>` nested
+// in other return types. Each one is expanded and called.
+use composerr::compose_errors;
+use std::{
+    future::Future,
+    io::Error as IoError,
+    num::ParseIntError,
+    pin::pin,
+    sync::Arc,
+    task::{Context, Poll, Waker},
+};
+
+#[derive(Debug, thiserror::Error)]
+#[error("out of range")]
+pub struct RangeError;
+
+// Polls a future without a runtime, the ones here never wait
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut context = Context::from_waker(Waker::noop());
+    match pin!(future).poll(&mut context) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("the future waited"),
+    }
+}
+
+#[compose_errors]
+#[errorset(RangeError)]
+const fn checked(n: u8) -> Result<u8, _> {
+    if n > 9 {
+        return Err(CheckedError::RangeError(RangeError));
+    }
+    Ok(n)
+}
+
+const CHECKED: Result<u8, CheckedError> = checked(3);
+
+#[compose_errors]
+#[errorset(RangeError)]
+unsafe fn unchecked(n: *const u8) -> Result<u8, _> {
+    Ok(unsafe { *n })
+}
+
+#[compose_errors]
+#[errorset(RangeError)]
+#[allow(improper_ctypes_definitions)] // A Rust caller, the qualifier is what's tested
+extern "C" fn exported(n: u8) -> Result<u8, _> {
+    if n > 9 {
+        Err(RangeError)?;
+    }
+    Ok(n)
+}
+
+#[compose_errors]
+#[errorset(ParseIntError)]
+async fn parsed(input: &str) -> Result<u8, _> {
+    Ok(input.parse()?)
+}
+
+#[test]
+fn qualifiers_are_kept() {
+    assert!(matches!(CHECKED, Ok(3)));
+    assert!(matches!(checked(10), Err(CheckedError::RangeError(_))));
+    assert_eq!(unsafe { unchecked(&4) }.unwrap(), 4);
+    assert!(matches!(exported(10), Err(ExportedError::RangeError(_))));
+    assert_eq!(block_on(parsed("5")).unwrap(), 5);
+    assert!(matches!(
+        block_on(parsed("x")),
+        Err(ParsedError::ParseIntError(_))
+    ));
+}
+
+#[compose_errors]
+#[errorset(ParseIntError)]
+fn generic<T>(input: &str) -> Result<T, _>
+where
+    T: From<u8> + Copy,
+{
+    Ok(T::from(input.parse::<u8>()?))
+}
+
+#[test]
+fn generics_and_where_clauses() {
+    assert_eq!(generic::<u32>("7").unwrap(), 7);
+    assert!(matches!(
+        generic::<u64>("x"),
+        Err(GenericError::ParseIntError(_))
+    ));
+}
+
+struct Parser {
+    radix: u32,
+}
+
+#[compose_errors]
+impl Parser {
+    #[errorset(ParseIntError)]
+    fn parse(&self, input: &str) -> Result<u32, _> {
+        Ok(u32::from_str_radix(input, self.radix)?)
+    }
+
+    #[errorset(ParseIntError)]
+    async fn parse_later(&self, input: &str) -> Result<u32, _> {
+        Ok(u32::from_str_radix(input, self.radix)?)
+    }
+
+    fn radix(&self) -> u32 {
+        self.radix
+    }
+}
+
+#[test]
+fn impl_block_methods() {
+    let parser = Parser { radix: 16 };
+    assert_eq!(parser.parse("ff").unwrap(), 255);
+    assert!(matches!(
+        parser.parse("z"),
+        Err(ParserImplParseError::ParseIntError(_))
+    ));
+    assert_eq!(block_on(parser.parse_later("10")).unwrap(), 16);
+    assert_eq!(parser.radix(), 16);
+}
+
+#[compose_errors]
+#[errorset(ParseIntError)]
+fn optional(input: Option<&str>) -> Option<Result<u8, _>> {
+    input.map(|input| Ok(input.parse()?))
+}
+
+#[compose_errors]
+#[errorset(ParseIntError)]
+fn polled(input: &str) -> Poll<Result<u8, _>> {
+    Poll::Ready(input.parse().map_err(PolledError::from))
+}
+
+#[compose_errors]
+#[errorset(ParseIntError)]
+fn listed(inputs: &[&str]) -> Vec<Result<u8, _>> {
+    inputs
+        .iter()
+        .map(|input| input.parse().map_err(ListedError::from))
+        .collect()
+}
+
+#[compose_errors]
+#[errorset(ParseIntError)]
+fn boxed(input: &str) -> Box<Result<u8, _>> {
+    Box::new(input.parse().map_err(BoxedError::from))
+}
+
+#[compose_errors]
+#[errorset(ParseIntError)]
+fn shared(input: &str) -> Arc<Result<u8, _>> {
+    Arc::new(input.parse().map_err(SharedError::from))
+}
+
+#[compose_errors]
+#[errorset(ParseIntError)]
+fn with_stats(input: &str) -> (Result<u8, _>, usize) {
+    (input.parse().map_err(WithStatsError::from), input.len())
+}
+
+#[compose_errors]
+#[errorset(ParseIntError)]
+fn paired(inputs: [&str; 2]) -> [Result<u8, _>; 2] {
+    inputs.map(|input| input.parse().map_err(PairedError::from))
+}
+
+#[compose_errors]
+#[errorset(ParseIntError)]
+fn each<'a>(inputs: &'a [&'a str]) -> impl Iterator<Item = Result<u8, _>> + 'a {
+    inputs
+        .iter()
+        .map(|input| input.parse().map_err(EachError::from))
+}
+
+#[test]
+fn results_nested_in_other_types() {
+    assert!(optional(None).is_none());
+    assert!(matches!(
+        optional(Some("x")),
+        Some(Err(OptionalError::ParseIntError(_)))
+    ));
+    assert!(matches!(
+        polled("x"),
+        Poll::Ready(Err(PolledError::ParseIntError(_)))
+    ));
+    assert!(matches!(
+        listed(&["1", "x"])[..],
+        [Ok(1), Err(ListedError::ParseIntError(_))]
+    ));
+    assert!(matches!(*boxed("x"), Err(BoxedError::ParseIntError(_))));
+    assert!(matches!(*shared("x"), Err(SharedError::ParseIntError(_))));
+    assert!(matches!(
+        with_stats("xy"),
+        (Err(WithStatsError::ParseIntError(_)), 2)
+    ));
+    assert!(matches!(
+        paired(["2", "x"]),
+        [Ok(2), Err(PairedError::ParseIntError(_))]
+    ));
+    let inputs = ["3", "x"];
+    let results: Vec<_> = each(&inputs).collect();
+    assert!(matches!(
+        results[..],
+        [Ok(3), Err(EachError::ParseIntError(_))]
+    ));
+}
+
+#[compose_errors]
+#[errorset(IoError)]
+fn core_path() -> core::result::Result<(), _> {
+    Err(IoError::other("core"))?
+}
+
+#[compose_errors]
+#[errorset(IoError)]
+fn std_path() -> ::std::result::Result<(), _> {
+    Err(IoError::other("std"))?
+}
+
+#[test]
+fn result_spelled_out() {
+    assert!(matches!(core_path(), Err(CorePathError::IoError(_))));
+    assert!(matches!(std_path(), Err(StdPathError::IoError(_))));
+}
@@ -0,0 +1,24 @@
+// The snafu backend, its context selectors next to the generated `From` impls.
+#![cfg(feature = "snafu")]
+use composerr::compose_errors;
+use snafu::ResultExt;
+use std::{error::Error, io::Error as IoError, num::ParseIntError};
+
+#[compose_errors(snafu)]
+#[errorset(IoError, ParseIntError)]
+fn load(text: &str) -> Result<u16, _> {
+    if text.is_empty() {
+        Err(IoError::other("empty")).context(IoSnafu)?;
+    }
+    Ok(text.parse()?)
+}
+
+#[test]
+fn selectors_and_question_mark() {
+    assert_eq!(load("80").unwrap(), 80);
+    let error = load("").unwrap_err();
+    assert!(matches!(error, LoadError::IoError { .. }));
+    assert_eq!(error.to_string(), "empty");
+    assert!(error.source().is_some());
+    assert!(matches!(load("x"), Err(LoadError::ParseIntError { .. })));
+}
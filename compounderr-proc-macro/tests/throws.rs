@@ -0,0 +1,71 @@
+// `errorset(throws)`: functions written returning `T`, their values wrapped in `Ok`.
+#![cfg(feature = "body-analysis")]
+
+use composerr::compose_errors;
+use std::{io::Error as IoError, num::ParseIntError};
+
+#[derive(Debug, thiserror::Error)]
+#[error("missing input")]
+pub struct Missing;
+
+#[compose_errors]
+#[errorset(throws, ParseIntError, Missing)]
+fn port(input: &str) -> u16 {
+    if input.is_empty() {
+        return Err(Missing);
+    }
+    if input == "default" {
+        return 8080;
+    }
+    let port: u16 = input.parse()?;
+    match port {
+        0 => return Err(PortError::Missing(Missing)),
+        1..=1023 => port + 8000,
+        _ => port,
+    }
+}
+
+#[test]
+fn returns_and_tail_are_wrapped() {
+    assert_eq!(port("default").unwrap(), 8080);
+    assert_eq!(port("80").unwrap(), 8080);
+    assert_eq!(port("9000").unwrap(), 9000);
+}
+
+#[test]
+fn errors_leave_by_return_and_question_mark() {
+    assert!(matches!(port(""), Err(PortError::Missing(_))));
+    assert!(matches!(port("0"), Err(PortError::Missing(_))));
+    assert!(matches!(port("x"), Err(PortError::ParseIntError(_))));
+}
+
+#[compose_errors]
+#[errorset(throws, IoError)]
+fn check(ok: bool) {
+    if !ok {
+        Err(IoError::other("failed"))?;
+    }
+}
+
+#[test]
+fn unit_functions_return_ok_unit() {
+    assert!(check(true).is_ok());
+    assert!(matches!(check(false), Err(CheckError::IoError(_))));
+}
+
+#[compose_errors]
+impl Missing {
+    #[errorset(throws, ParseIntError)]
+    fn parse(&self, input: &str) -> u8 {
+        input.parse()?
+    }
+}
+
+#[test]
+fn methods_throw_too() {
+    assert_eq!(Missing.parse("4").unwrap(), 4);
+    assert!(matches!(
+        Missing.parse("x"),
+        Err(MissingImplParseError::ParseIntError(_))
+    ));
+}
@@ -0,0 +1,73 @@
+// Trait methods with `where` clauses and opaque return types, their bounds kept as written.
+use composerr::compose_errors;
+use std::{
+    future::Future,
+    num::ParseIntError,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+// Polls a future without a runtime, the ones here never wait
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut context = Context::from_waker(Waker::noop());
+    match pin!(future).poll(&mut context) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("the future waited"),
+    }
+}
+
+#[compose_errors]
+trait Fetch {
+    fn raw(&self) -> &str;
+
+    #[errorset(ParseIntError)]
+    fn fetch(&self) -> impl Future<Output = Result<u8, _>> + Send;
+
+    #[errorset(ParseIntError)]
+    fn fetch_all(&self) -> impl Iterator<Item = Result<u8, _>> + '_ {
+        self.raw().split(',').map(|part| Ok(part.parse()?))
+    }
+
+    #[errorset(ParseIntError)]
+    fn into_number(self) -> Result<u8, _>
+    where
+        Self: Sized,
+    {
+        Ok(self.raw().parse()?)
+    }
+}
+
+struct Text(&'static str);
+
+impl Fetch for Text {
+    fn raw(&self) -> &str {
+        self.0
+    }
+
+    fn fetch(&self) -> impl Future<Output = Result<u8, FetchTraitFetchError>> + Send {
+        let raw = self.0;
+        async move { Ok(raw.parse()?) }
+    }
+}
+
+fn assert_send<T: Send>(value: T) -> T {
+    value
+}
+
+#[test]
+fn bounds_are_kept() {
+    assert_eq!(block_on(assert_send(Text("4").fetch())).unwrap(), 4);
+    assert!(matches!(
+        block_on(Text("x").fetch()),
+        Err(FetchTraitFetchError::ParseIntError(_))
+    ));
+    let all: Vec<_> = Text("1,x").fetch_all().collect();
+    assert!(matches!(
+        all[..],
+        [Ok(1), Err(FetchTraitFetchAllError::ParseIntError(_))]
+    ));
+    assert!(matches!(
+        Text("x").into_number(),
+        Err(FetchTraitIntoNumberError::ParseIntError(_))
+    ));
+}
@@ -0,0 +1,61 @@
+// `errorset(typed)`, the boxed signature kept and a `_typed` sibling returning the enum.
+use composerr::compose_errors;
+use std::{error::Error, io::Error as IoError, num::ParseIntError};
+
+type BoxError = Box<dyn Error + Send + Sync>;
+
+#[derive(Debug, thiserror::Error)]
+#[error("not a member")]
+pub struct Stranger;
+
+#[compose_errors]
+#[errorset(typed, IoError, ParseIntError)]
+fn fetch(input: &str) -> Result<u8, Box<dyn Error + Send + Sync>> {
+    match input {
+        "" => Err(IoError::other("empty"))?,
+        "?" => Err(Stranger)?,
+        _ => Ok(input.parse()?),
+    }
+}
+
+#[test]
+fn boxed_function_is_kept() {
+    let error: BoxError = fetch("x").unwrap_err();
+    assert!(error.downcast_ref::<ParseIntError>().is_some());
+    assert_eq!(fetch("3").unwrap(), 3);
+}
+
+#[test]
+fn typed_sibling_downcasts_members() {
+    assert_eq!(fetch_typed("3").unwrap(), 3);
+    assert!(matches!(fetch_typed(""), Err(FetchError::IoError(_))));
+    assert!(matches!(
+        fetch_typed("x"),
+        Err(FetchError::ParseIntError(_))
+    ));
+    let error = fetch_typed("?").unwrap_err();
+    let FetchError::Other(other) = &error else {
+        panic!("expected the boxed error, got {error:?}");
+    };
+    assert!(other.downcast_ref::<Stranger>().is_some());
+    assert_eq!(error.to_string(), "not a member");
+}
+
+struct Client;
+
+#[compose_errors]
+impl Client {
+    #[errorset(typed, ParseIntError)]
+    fn parse(&self, input: &str) -> Result<u8, Box<dyn Error + Send + Sync>> {
+        Ok(input.parse()?)
+    }
+}
+
+#[test]
+fn methods_get_a_sibling_too() {
+    assert!(Client.parse("x").is_err());
+    assert!(matches!(
+        Client.parse_typed("x"),
+        Err(ClientImplParseError::ParseIntError(_))
+    ));
+}
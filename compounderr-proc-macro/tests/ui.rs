@@ -0,0 +1,10 @@
+// Diagnostics of the macro, compared against the `.stderr` next to each case.
+// `TRYBUILD=overwrite cargo test --test ui` refreshes them after a wording change.
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+    // Options rewriting bodies, only accepted with the feature
+    #[cfg(feature = "body-analysis")]
+    cases.compile_fail("tests/ui/body/*.rs");
+}
@@ -0,0 +1,10 @@
+use composerr::compose_errors;
+use std::num::ParseIntError;
+
+#[compose_errors]
+#[errorset(throws, ParseIntError)]
+fn digits(input: &str) -> impl Iterator<Item = u8> + '_ {
+    input.bytes()
+}
+
+fn main() {}
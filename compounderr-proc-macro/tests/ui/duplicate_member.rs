@@ -0,0 +1,11 @@
+use composerr::compose_errors;
+use std::{fmt::Error as FmtError, io::Error as IoError};
+
+#[compose_errors]
+#[errorset(IoError, FmtError)]
+#[errorset(IoError)]
+fn load() -> Result<(), _> {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use composerr::compose_errors;
+use std::io::Error as IoError;
+
+#[compose_errors]
+#[errorset(IoError)]
+fn count() -> usize {
+    0
+}
+
+#[compose_errors]
+#[errorset(IoError)]
+fn run() {}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use composerr::compose_errors;
+use std::fmt::Error as FmtError;
+
+#[compose_errors]
+#[errorset(extend, FmtError)]
+fn load() -> Option<u8> {
+    None
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use composerr::compose_errors;
+use std::io::Error as IoError;
+
+#[compose_errors]
+#[errorset(IoError)]
+fn run<E: From<IoError>>(fallback: E) -> Result<(), E> {
+    Err(fallback)
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use composerr::compose_errors;
+
+#[compose_errors]
+#[errorset(io::NotFund, IoOther)]
+fn load() -> Result<String, _> {
+    Ok(String::new())
+}
+
+fn main() {}
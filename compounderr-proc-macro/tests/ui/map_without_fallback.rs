@@ -0,0 +1,20 @@
+use composerr::compose_errors;
+
+#[derive(Debug, thiserror::Error)]
+pub enum DbError {
+    #[error("no rows")]
+    RowNotFound,
+    #[error("pool closed")]
+    PoolClosed,
+}
+
+#[compose_errors]
+#[errorset(map(DbError => {
+    RowNotFound => NotFound,
+    PoolClosed => Unavailable,
+}))]
+fn fetch() -> Result<(), _> {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use composerr::compose_errors;
+use std::io::Error as IoError;
+
+#[compose_errors(track_caller)]
+#[errorset(newtype, IoError)]
+fn open() -> Result<(), _> {
+    Ok(())
+}
+
+#[compose_errors(visitor)]
+#[errorset(newtype, IoError)]
+fn close() -> Result<(), _> {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use composerr::compose_errors;
+use std::{fmt::Error as FmtError, io::Error as IoError};
+
+#[compose_errors]
+#[errorset(IoError(fatal), FmtError(warning))]
+fn run() -> Result<(), _> {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use composerr::compose_errors;
+use std::io::Error as IoError;
+
+#[compose_errors]
+#[errorset(IoError, no_try_frm)]
+fn load() -> Result<(), _> {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use composerr::compose_errors;
+use std::io::Error as IoError;
+
+#[compose_errors]
+#[errorset(IoError, nmae = "LoadFailed")]
+fn load() -> Result<(), _> {
+    Ok(())
+}
+
+fn main() {}
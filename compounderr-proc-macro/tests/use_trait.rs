@@ -0,0 +1,83 @@
+// `use_trait`, implementations of a composed trait leaving `_` for the trait's enums.
+use composerr::compose_errors;
+use std::{io::Error as IoError, num::ParseIntError};
+
+#[compose_errors]
+trait Source {
+    #[errorset(IoError, ParseIntError)]
+    fn read(&self) -> Result<u8, _>;
+
+    fn name(&self) -> Result<String, String>;
+
+    #[errorset(IoError)]
+    fn close(self) -> Result<(), _>
+    where
+        Self: Sized;
+}
+
+struct Text(&'static str);
+
+#[compose_errors(use_trait)]
+impl Source for Text {
+    fn read(&self) -> Result<u8, _> {
+        if self.0.is_empty() {
+            Err(IoError::other("empty"))?;
+        }
+        Ok(self.0.parse()?)
+    }
+
+    fn name(&self) -> Result<String, String> {
+        Ok("text".into())
+    }
+
+    fn close(self) -> Result<(), _> {
+        Ok(())
+    }
+}
+
+#[test]
+fn impl_methods_return_the_trait_enums() {
+    assert_eq!(Text("7").read().unwrap(), 7);
+    let error: SourceTraitReadError = Text("").read().unwrap_err();
+    assert!(matches!(error, SourceTraitReadError::IoError(_)));
+    assert!(matches!(
+        Text("x").read(),
+        Err(SourceTraitReadError::ParseIntError(_))
+    ));
+    assert_eq!(Text("").name().unwrap(), "text");
+    let closed: Result<(), SourceTraitCloseError> = Text("").close();
+    assert!(closed.is_ok());
+}
+
+// Through a trait object, the signatures are the trait's
+#[test]
+fn callable_as_dyn_trait() {
+    let source: &dyn Source = &Text("x");
+    assert!(matches!(
+        source.read(),
+        Err(SourceTraitReadError::ParseIntError(_))
+    ));
+}
+
+#[compose_errors(module = errors)]
+trait Sink {
+    #[errorset(IoError)]
+    fn write(&mut self, byte: u8) -> Result<(), _>;
+}
+
+struct Null;
+
+#[compose_errors(use_trait, module = errors)]
+impl Sink for Null {
+    fn write(&mut self, _: u8) -> Result<(), _> {
+        Err(IoError::other("full"))?
+    }
+}
+
+#[test]
+fn module_option_is_followed() {
+    assert!(matches!(
+        Null.write(1),
+        Err(errors::SinkTraitWriteError::IoError(_))
+    ));
+}
@@ -0,0 +1,45 @@
+// `visitor`, a visitor trait per enum and `visit` handing the error held to the matching method.
+use composerr::compose_errors;
+use std::{io::Error as IoError, num::ParseIntError};
+
+#[compose_errors(visitor)]
+#[errorset(IoError, ParseIntError, Timeout { secs: u64 } : "timed out after {secs}s", Busy : "busy")]
+fn fetch(input: &str) -> Result<u8, _> {
+    match input {
+        "" => Err(IoError::other("empty"))?,
+        "late" => Err(FetchError::Timeout { secs: 30 })?,
+        "busy" => Err(FetchError::Busy)?,
+        _ => Ok(input.parse()?),
+    }
+}
+
+struct Status;
+
+impl FetchErrorVisitor for Status {
+    type Output = String;
+
+    fn visit_io_error(self, error: IoError) -> String {
+        format!("503 {error}")
+    }
+
+    fn visit_parse_int_error(self, _: ParseIntError) -> String {
+        "400".into()
+    }
+
+    fn visit_timeout(self, secs: u64) -> String {
+        format!("504 after {secs}s")
+    }
+
+    fn visit_busy(self) -> String {
+        "429".into()
+    }
+}
+
+#[test]
+fn each_variant_reaches_its_method() {
+    let status = |input| fetch(input).unwrap_err().visit(Status);
+    assert_eq!(status(""), "503 empty");
+    assert_eq!(status("x"), "400");
+    assert_eq!(status("late"), "504 after 30s");
+    assert_eq!(status("busy"), "429");
+}